@@ -43,7 +43,7 @@ fn main() {
         Err(ref e) => println!("{:?}", e),
     }
 
-    assert_eq!(unif.is_ok(), true);
+    assert!(unif.is_ok());
     let ines_c  = unif.unwrap().into_ines();
 
     match ines_c {
@@ -72,10 +72,8 @@ fn main() {
             println!(" fds rom =========================================================");
             println!(" \tfile -----------------------------------------------");
 
-            for f in &fds.disk_files {
-                let fname = unsafe { 
-                    String::from_utf8_unchecked(f.file_name.to_vec())
-                };
+            for f in fds.disk_files() {
+                let fname = f.name();
                 println!("\t\tdisk num: {:?} actual disk num: {:?}", f.disk_number, f.actual_disk_number);
                 println!("\t\tside num: {:?}", f.side_number);
                 println!("\t\tfile number: {:?}", f.file_number);
@@ -0,0 +1,82 @@
+// crc-16 with the reflected ccitt polynomial (x^16 + x^12 + x^5 + 1) as used by the famicom disk system
+
+pub const CCITT_POLYNOMIAL: u16 = 0x8408;
+
+pub const fn generate_crc_table(polynomial: u16) -> [u16; 256] {
+    let mut table: [u16; 256] = [0; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u16;
+        let mut j = 0;
+        while j < 8 {
+            if (c & 1) > 0 {
+                c = polynomial ^ (c >> 1);
+            }
+            else {
+                c >>= 1;
+            }
+            j += 1;
+        }
+
+        table[i] = c;
+        i += 1;
+    }
+
+    table
+}
+
+const CCITT_TABLE: [u16; 256] = generate_crc_table(CCITT_POLYNOMIAL);
+
+pub fn crc16_from_slice(crc: u16, data: &[u8], poly_table: &[u16; 256]) -> u16 {
+    let mut crc = crc;
+    for i in data {
+        crc = (crc >> 8) ^ poly_table[((crc ^ *i as u16) & 0xFF) as usize];
+    }
+
+    crc
+}
+
+// the disk crc also covers the 0x80 start mark written in front of every block
+pub fn crc16_fds_from_slice(data: &[u8]) -> u16 {
+    let crc = crc16_from_slice(0, &[0x80], &CCITT_TABLE);
+    crc16_from_slice(crc, data, &CCITT_TABLE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // bit serial form of the disk crc, the register starts with the start mark bit set
+    // and the block is followed by two zero bytes to flush it
+    fn fds_crc_bit_serial(block: &[u8]) -> u16 {
+        let mut sum: u16 = 0x8000;
+        for byte in block.iter().chain([0, 0].iter()) {
+            for bit in 0..8 {
+                let carry = sum & 1;
+                sum = (sum >> 1) | ((((*byte >> bit) & 1) as u16) << 15);
+                if carry != 0 {
+                    sum ^= CCITT_POLYNOMIAL;
+                }
+            }
+        }
+        sum
+    }
+
+    #[test]
+    fn kermit_check_value() {
+        // catalogued check value of crc-16/kermit
+        assert_eq!(crc16_from_slice(0, b"123456789", &CCITT_TABLE), 0x2189);
+    }
+
+    #[test]
+    fn fds_crc_matches_bit_serial_form() {
+        let mut block_1 = b"\x01*NINTENDO-HVC*".to_vec();
+        block_1.resize(56, 0x5A);
+        let block_4: Vec<u8> = (0..=0x1000u32).map(|i| (i * 7) as u8).collect();
+
+        for block in [&[0x02, 0x03][..], &block_1, &block_4, &[][..]] {
+            assert_eq!(crc16_fds_from_slice(block), fds_crc_bit_serial(block));
+        }
+    }
+}
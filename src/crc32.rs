@@ -14,7 +14,7 @@ pub fn generate_crc_table(polynomial: u32) -> [u32; 256] {
                 c = polynomial ^ (c >> 1);
             }
             else {
-                c = c >> 1;
+                c >>= 1;
             }
         }
 
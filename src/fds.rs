@@ -1,20 +1,263 @@
 use std::io;
 use std::io::prelude::*;
-use num_derive::FromPrimitive;    
-use num_traits::FromPrimitive;
-use super::RomError;
+use super::{RomError,ErrorKind,RomFormat,RomInfo,Mirroring,Region,crc16};
 
 const HEADER_SIZE: u64 = 16;
-const DISK_SIZE: u64 = 65500;
+pub const DISK_SIZE: u64 = 65500;
+// .qd disk side, blocks are followed by their crc
+pub const QD_DISK_SIZE: u64 = 65536;
 const BLOCK_1_SIZE: u64 = 56;
 const BLOCK_2_SIZE: u64 = 2;
-const BLOCK_3_SIZE: u64 = 17;
+const BLOCK_3_SIZE: u64 = 16;
+const CRC_SIZE: u64 = 2;
+// gaps of a real disk in bytes, 28300 bits in front of block 1 and 976 bits between blocks
+const RAW_LEADING_GAP: usize = 28300 / 8;
+const RAW_BLOCK_GAP: usize = 976 / 8;
+const RAW_START_MARK: u8 = 0x80;
+// "FDSP"
+const PATCH_GUARD: [u8; 4] = [0x46, 0x44, 0x53, 0x50];
+const PATCH_VERSION: u8 = 1;
+const PRG_RAM_START: usize = 0x6000;
+const PRG_RAM_SIZE: usize = 32 * 1024;
+const CHR_RAM_SIZE: usize = 8 * 1024;
+const NAMETABLES_SIZE: usize = 4 * 1024;
 
-#[derive(Debug, Copy, Clone, PartialEq, FromPrimitive, ToPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum KindOfFile {
-    PrgRam = 0,
-    ChrRam = 1,
-    Nametable = 2,
+    PrgRam,
+    ChrRam,
+    Nametable,
+    Other(u8),
+}
+
+impl KindOfFile {
+    pub fn from_u8(code: u8) -> KindOfFile {
+        match code {
+            0 => KindOfFile::PrgRam,
+            1 => KindOfFile::ChrRam,
+            2 => KindOfFile::Nametable,
+            _ => KindOfFile::Other(code),
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            KindOfFile::PrgRam => 0,
+            KindOfFile::ChrRam => 1,
+            KindOfFile::Nametable => 2,
+            KindOfFile::Other(code) => code,
+        }
+    }
+}
+
+// damage found while reading a disk side, the side is loaded as far as possible
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiskDiagnostic {
+    // a block other than a file header where the next file or the zero filled end of disk was expected
+    UnexpectedBlockCode { offset: u64, found: u8 },
+    UnknownFileKind { file_number: u8, kind: u8 },
+    // file header not followed by its data block, the file is dropped
+    MissingFileData { file_number: u8, offset: u64, found: u8 },
+    // file data cut short by the end of the image, the rest is zero filled
+    TruncatedFile { file_number: u8, offset: u64, expected: u16, found: u16 },
+    // fewer files found than declared in block 2
+    MissingFiles { declared: u8, found: u32 },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GameType {
+    Normal,
+    Event,
+    Reduction,
+    Other(u8),
+}
+
+impl GameType {
+    pub fn from_u8(code: u8) -> GameType {
+        match code {
+            b' ' => GameType::Normal,
+            b'E' => GameType::Event,
+            b'R' => GameType::Reduction,
+            _ => GameType::Other(code),
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            GameType::Normal => b' ',
+            GameType::Event => b'E',
+            GameType::Reduction => b'R',
+            GameType::Other(code) => code,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DiskType {
+    FMC,
+    FSC,
+    Other(u8),
+}
+
+impl DiskType {
+    pub fn from_u8(code: u8) -> DiskType {
+        match code {
+            0 => DiskType::FMC,
+            1 => DiskType::FSC,
+            _ => DiskType::Other(code),
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            DiskType::FMC => 0,
+            DiskType::FSC => 1,
+            DiskType::Other(code) => code,
+        }
+    }
+}
+
+// bcd encoded date, the year counts from the start of the showa or heisei era
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FdsDate {
+    pub year: u8,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl FdsDate {
+    /// Gregorian (year, month, day), None if not valid bcd or an unset date
+    /// 
+    /// # Examples
+    /// 
+    ///  ```
+    /// let date = nes_rom::fds::FdsDate { year: 0x61, month: 0x02, day: 0x21 };
+    /// assert_eq!(date.to_gregorian(), Some((1986, 2, 21)));
+    ///  ```
+    pub fn to_gregorian(&self) -> Option<(u16, u8, u8)> {
+        let year = bcd_to_u8(self.year)? as u16;
+        let month = bcd_to_u8(self.month)?;
+        let day = bcd_to_u8(self.day)?;
+        if month == 0 || month > 12 || day == 0 || day > 31 {
+            return None;
+        }
+
+        // the disk system was sold from showa 61 (1986) into the heisei era (1989-)
+        if year >= 58 {
+            Some((1925 + year, month, day))
+        }
+        else {
+            Some((1988 + year, month, day))
+        }
+    }
+}
+
+/// Disk side information from block 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskInfo {
+    pub verification: [u8; 14],
+    pub manufacturer_code: u8,
+    pub game_name: [u8; 3],
+    pub game_type: GameType,
+    pub revision: u8,
+    pub side_number: u8,
+    pub disk_number: u8,
+    pub disk_type: DiskType,
+    pub boot_read_file_code: u8,
+    pub manufacturing_date: FdsDate,
+    pub country_code: u8,
+    pub rewrite_date: FdsDate,
+    pub disk_writer_serial: u16,
+    /// bcd encoded
+    pub rewrite_count: u8,
+    pub actual_disk_side: u8,
+    pub price: u8,
+    /// raw block 1, bytes not decoded into fields are written back from here
+    pub raw: [u8; BLOCK_1_SIZE as usize],
+}
+
+impl DiskInfo {
+    pub fn new() -> DiskInfo {
+        let mut raw: [u8; BLOCK_1_SIZE as usize] = [0; BLOCK_1_SIZE as usize];
+        raw[0..15].copy_from_slice(&super::FDS_DISK_GUARD);
+        // fixed values found on licensed disks
+        raw[0x1A..0x1F].copy_from_slice(&[0xFF; 5]);
+        raw[0x23] = 0x61;
+        raw[0x26] = 0x02;
+        raw[0x30] = 0x80;
+        raw[0x33] = 0x07;
+        DiskInfo::from_block(&raw)
+    }
+
+    fn from_block(buf: &[u8; BLOCK_1_SIZE as usize]) -> DiskInfo {
+        let mut verification: [u8; 14] = [0; 14];
+        verification.copy_from_slice(&buf[1..15]);
+        let mut game_name: [u8; 3] = [0; 3];
+        game_name.copy_from_slice(&buf[0x10..0x13]);
+
+        DiskInfo {
+            verification,
+            manufacturer_code: buf[0x0F],
+            game_name,
+            game_type: GameType::from_u8(buf[0x13]),
+            revision: buf[0x14],
+            side_number: buf[0x15],
+            disk_number: buf[0x16],
+            disk_type: DiskType::from_u8(buf[0x17]),
+            boot_read_file_code: buf[0x19],
+            manufacturing_date: FdsDate { year: buf[0x1F], month: buf[0x20], day: buf[0x21] },
+            country_code: buf[0x22],
+            rewrite_date: FdsDate { year: buf[0x2C], month: buf[0x2D], day: buf[0x2E] },
+            disk_writer_serial: ((buf[0x32] as u16) << 8) | buf[0x31] as u16,
+            rewrite_count: buf[0x34],
+            actual_disk_side: buf[0x35],
+            price: buf[0x37],
+            raw: *buf,
+        }
+    }
+
+    fn to_block(&self) -> [u8; BLOCK_1_SIZE as usize] {
+        let mut buf = self.raw;
+        buf[0] = 0x01;
+        buf[1..15].copy_from_slice(&self.verification);
+        buf[0x0F] = self.manufacturer_code;
+        buf[0x10..0x13].copy_from_slice(&self.game_name);
+        buf[0x13] = self.game_type.to_u8();
+        buf[0x14] = self.revision;
+        buf[0x15] = self.side_number;
+        buf[0x16] = self.disk_number;
+        buf[0x17] = self.disk_type.to_u8();
+        buf[0x19] = self.boot_read_file_code;
+        buf[0x1F..0x22].copy_from_slice(&[self.manufacturing_date.year, self.manufacturing_date.month, self.manufacturing_date.day]);
+        buf[0x22] = self.country_code;
+        buf[0x2C..0x2F].copy_from_slice(&[self.rewrite_date.year, self.rewrite_date.month, self.rewrite_date.day]);
+        buf[0x31..0x33].copy_from_slice(&self.disk_writer_serial.to_le_bytes());
+        buf[0x34] = self.rewrite_count;
+        buf[0x35] = self.actual_disk_side;
+        buf[0x37] = self.price;
+        buf
+    }
+
+    /// true if the verification string is "*NINTENDO-HVC*", the bios refuses disks without it
+    pub fn is_verified(&self) -> bool {
+        self.verification[..] == super::FDS_DISK_GUARD[1..]
+    }
+
+    /// Game name decoded with `decode_text`
+    pub fn name(&self) -> String {
+        decode_text(&self.game_name)
+    }
+
+    /// Licensee name of the manufacturer code, None for unknown codes
+    pub fn licensee(&self) -> Option<&'static str> {
+        licensee_name(self.manufacturer_code)
+    }
+}
+
+impl Default for DiskInfo {
+    fn default() -> Self {
+        DiskInfo::new()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -30,6 +273,8 @@ pub struct FileData {
     pub file_size: u16,
     pub file_type: KindOfFile,
     pub file_data: Vec<u8>,
+    // stored past the file amount declared in block 2, the bios never loads it but games can read it directly
+    pub hidden: bool,
 }
 
 impl FileData {
@@ -46,135 +291,1296 @@ impl FileData {
             file_size: 0,
             file_type: KindOfFile::PrgRam,
             file_data: Vec::new(),
+            hidden: false,
+        }
+    }
+
+    fn from_header_block(buf: &[u8; BLOCK_3_SIZE as usize]) -> FileData {
+        let mut fd = FileData::new();
+        fd.file_number = buf[1];
+        fd.file_id = buf[2];
+        fd.file_name.copy_from_slice(&buf[3..11]);
+        fd.file_address = ((buf[12] as u16) << 8) | buf[11] as u16;
+        fd.file_size = ((buf[14] as u16) << 8) | buf[13] as u16;
+        fd.file_type = KindOfFile::from_u8(buf[15]);
+        fd
+    }
+
+    // block 3, the file size is taken from the file data
+    fn header_block(&self) -> [u8; BLOCK_3_SIZE as usize] {
+        let mut buf: [u8; BLOCK_3_SIZE as usize] = [0; BLOCK_3_SIZE as usize];
+        buf[0] = 0x03;
+        buf[1] = self.file_number;
+        buf[2] = self.file_id;
+        buf[3..11].copy_from_slice(&self.file_name);
+        buf[11..13].copy_from_slice(&self.file_address.to_le_bytes());
+        buf[13..15].copy_from_slice(&(self.file_data.len() as u16).to_le_bytes());
+        buf[15] = self.file_type.to_u8();
+        buf
+    }
+
+    /// File name decoded with `decode_text`
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let fd = nes_rom::fds::FileData::new();
+    /// println!("file name: {}", fd.name());
+    ///  ```
+    pub fn name(&self) -> String {
+        decode_text(&self.file_name)
+    }
+}
+
+impl Default for FileData {
+    fn default() -> Self {
+        FileData::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskSide {
+    pub disk_info: DiskInfo,
+    // file amount declared in block 2
+    pub file_amount: u8,
+    // files in on-disk order
+    pub files: Vec<FileData>,
+    pub diagnostics: Vec<DiskDiagnostic>,
+}
+
+impl DiskSide {
+    pub fn new() -> DiskSide {
+        DiskSide {
+            disk_info: DiskInfo::new(),
+            file_amount: 0,
+            files: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Files stored past the file amount declared in block 2, used as copy protection by some games
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let side = nes_rom::fds::DiskSide::new();
+    /// for fd in side.hidden_files() {
+    ///     println!("hidden file {}", fd.file_number);
+    /// }
+    ///  ```
+    pub fn hidden_files(&self) -> impl Iterator<Item = &FileData> {
+        self.files.iter().filter(|fd| fd.hidden)
+    }
+}
+
+impl Default for DiskSide {
+    fn default() -> Self {
+        DiskSide::new()
+    }
+}
+
+// files are identified by their on-disk position, the way games write them back
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchChange {
+    FileAmount { side: u8, file_amount: u8 },
+    // file written over the file at index or appended after the last one
+    File { side: u8, index: u16, file: FileData },
+    // files from index on removed
+    Truncate { side: u8, index: u16 },
+}
+
+/// Changes between two images of the same disk, e.g. the pristine disk and the disk after the game saved to it
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FdsPatch {
+    pub changes: Vec<PatchChange>,
+}
+
+/// Ram adapter and ppu memory after the bios loaded the boot files of a disk side
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootImage {
+    // cpu $6000-$DFFF
+    pub prg_ram: Vec<u8>,
+    // ppu $0000-$1FFF
+    pub chr_ram: Vec<u8>,
+    // ppu $2000-$2FFF, without mirroring applied
+    pub nametables: Vec<u8>,
+}
+
+impl BootImage {
+    pub fn new() -> BootImage {
+        BootImage {
+            prg_ram: vec![0; PRG_RAM_SIZE],
+            chr_ram: vec![0; CHR_RAM_SIZE],
+            nametables: vec![0; NAMETABLES_SIZE],
+        }
+    }
+
+    /// Copy a file to its load address, bytes landing outside of the ram modelled here (e.g. console ram at $0000-$07FF) are dropped
+    pub fn load_file(&mut self, fd: &FileData) {
+        for (i, byte) in fd.file_data.iter().enumerate() {
+            let address = fd.file_address as usize + i;
+            let target = match fd.file_type {
+                KindOfFile::PrgRam => {
+                    if !(PRG_RAM_START..PRG_RAM_START + PRG_RAM_SIZE).contains(&address) {
+                        continue;
+                    }
+                    &mut self.prg_ram[address - PRG_RAM_START]
+                },
+                // everything but cpu files is written through the ppu
+                _ => {
+                    if address < CHR_RAM_SIZE {
+                        &mut self.chr_ram[address]
+                    }
+                    else if address < CHR_RAM_SIZE + NAMETABLES_SIZE {
+                        &mut self.nametables[address - CHR_RAM_SIZE]
+                    }
+                    else {
+                        continue;
+                    }
+                },
+            };
+            *target = *byte;
         }
     }
 }
 
+impl Default for BootImage {
+    fn default() -> Self {
+        BootImage::new()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Fds {
-    pub sides_amount: u8,
-    pub disk_files: Vec<FileData>,
+    // write the 16 byte fwNES header, false for raw disk side images
+    pub fwnes_header: bool,
+    pub sides: Vec<DiskSide>,
 }
 
 impl Fds {
     pub fn new() -> Fds {
         Fds {
-            sides_amount: 0,
-            disk_files: Vec::new(),
+            fwnes_header: true,
+            sides: Vec::new(),
         }
     }
 
-    /// Load and parse .fds file
+    /// All files of all disk sides in on-disk order
     /// 
     /// # Examples
     /// 
+    ///  ```no_run
+    /// # let fds = nes_rom::fds::Fds::new();
+    /// for fd in fds.disk_files() {
+    ///     println!("{:?}", fd.file_name);
+    /// }
     ///  ```
+    pub fn disk_files(&self) -> impl Iterator<Item = &FileData> {
+        self.sides.iter().flat_map(|side| side.files.iter())
+    }
+
+    /// Load and parse .fds file, with or without the fwNES header, or .qd file.
+    /// The block crcs of .qd files are verified
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let arg = std::fs::File::open("rom.fds").unwrap();
     /// let fds = nes_rom::fds::Fds::from_rom(arg);
     ///  ``` 
     pub fn from_rom<R: Read + Seek>(mut file: R) -> Result<Fds, RomError> {
         let mut buf: [u8; 16] = [0; 16];
-        file.read_exact(&mut buf)?;
-        // check header
-        if &buf[0..4] != super::FDS_GUARD {
-            return Err(RomError::InvalidFormat);
-        }  
+        file.read_exact(&mut buf).map_err(|e| RomError::from(e).with_format(RomFormat::Fds).with_field("header").with_offset(0))?;
 
         let mut fds = Fds::new();
-        fds.sides_amount = buf[4];
-
-        let cur_pos = file.seek(io::SeekFrom::Current(0))?;
         let file_size = file.seek(io::SeekFrom::End(0))?;
-        file.seek(io::SeekFrom::Start(cur_pos))?;
+        let mut qd = false;
 
-        if file_size < (fds.sides_amount as u64 * DISK_SIZE) + 16 {
-            return Err(RomError::InvalidRom);
+        let sides_amount = if buf[0..4] == super::FDS_GUARD {
+            if file_size < (buf[4] as u64 * DISK_SIZE) + HEADER_SIZE {
+                return Err(fds_error(ErrorKind::InvalidRom, "disk sides").with_offset(4)
+                    .with_values(format!("{} sides of {} bytes", buf[4], DISK_SIZE), format!("file size {}", file_size)));
+            }
+            buf[4]
         }
+        // headerless image, raw disk sides starting with block 1
+        else if buf[0..15] == super::FDS_DISK_GUARD {
+            fds.fwnes_header = false;
+            // .qd images have the crc of block 1 in front of block 2
+            let mut block_2: [u8; 3] = [0; 3];
+            file.seek(io::SeekFrom::Start(BLOCK_1_SIZE))?;
+            if file.read_exact(&mut block_2).is_ok() {
+                qd = block_2[0] != 0x02 && block_2[2] == 0x02;
+            }
+
+            // the last side may be cut short after its last file, a partial side
+            // only counts if it starts with block 1 and is not just padding
+            let side_size = if qd { QD_DISK_SIZE } else { DISK_SIZE };
+            let mut sides = file_size / side_size;
+            if file_size % side_size != 0 {
+                let mut guard: [u8; 15] = [0; 15];
+                file.seek(io::SeekFrom::Start(sides * side_size))?;
+                if file.read_exact(&mut guard).is_ok() && guard == super::FDS_DISK_GUARD {
+                    sides += 1;
+                }
+            }
+            if sides > 0xFF {
+                return Err(fds_error(ErrorKind::InvalidRom, "disk sides")
+                    .with_values("at most 255 sides", sides));
+            }
+            sides as u8
+        }
+        else {
+            return Err(fds_error(ErrorKind::InvalidFormat, "magic").with_offset(0)
+                .with_values(format!("{:02X?} or block 1", super::FDS_GUARD), format!("{:02X?}", &buf[0..4])));
+        };
 
+        let data_offset = if fds.fwnes_header { HEADER_SIZE } else { 0 };
+        let side_size = if qd { QD_DISK_SIZE } else { DISK_SIZE };
         let mut actual_disk_num = -1;
-        for i in 0..fds.sides_amount {
-            file.seek(io::SeekFrom::Start(HEADER_SIZE + (i as u64 * DISK_SIZE)))?;
+        for i in 0..sides_amount {
+            let side_offset = data_offset + (i as u64 * side_size);
+            file.seek(io::SeekFrom::Start(side_offset))?;
 
             if i%2 == 0 {
                 actual_disk_num += 1;
             }
 
-            Fds::read_disk_side(&mut file, &mut fds.disk_files, actual_disk_num as u8)?;
+            fds.sides.push(Fds::read_disk_side(&mut file, actual_disk_num as u8, i, side_offset, qd)?);
         }
         
         Ok(fds)
      }
 
-     fn read_disk_side<R: Read>(file: &mut R, fv: &mut Vec<FileData>, actual_disk_num: u8) -> Result<(), RomError> {
+     fn read_disk_side<R: Read + Seek>(file: &mut R, actual_disk_num: u8, side: u8, side_offset: u64, qd: bool) -> Result<DiskSide, RomError> {
          // block 1
         let mut buf1: [u8; BLOCK_1_SIZE as usize] = [0; BLOCK_1_SIZE as usize];
-        file.read_exact(&mut buf1)?;
+        file.read_exact(&mut buf1).map_err(|e| RomError::from(e).with_format(RomFormat::Fds).with_field(format!("side {} block 1", side)).with_offset(side_offset))?;
         // check block code
         if buf1[0] != 1 {
-            return Err(RomError::InvalidRom);
+            return Err(fds_error(ErrorKind::InvalidRom, format!("side {} block 1", side)).with_offset(side_offset).with_values(1, buf1[0]));
+        }
+        if qd {
+            Fds::read_block_crc(file, &buf1, format!("side {} block 1", side))?;
         }
 
+        let disk_info = DiskInfo::from_block(&buf1);
         // If the FDS is started with a disk whose side number and disk number aren't both $00, it will be prompted to insert the first disk side. 
         // However, some games make this number $00, even for the second disk to make it bootable too.
-        let side_num = buf1[21];
-        let disk_num = buf1[22];
+        let side_num = disk_info.side_number;
+        let disk_num = disk_info.disk_number;
         // All files with IDs smaller or equals to the boot read file code will be loaded when the game is booting.
-        let boot_read_file_code = buf1[25];
+        let boot_read_file_code = disk_info.boot_read_file_code;
+        let mut disk_side = DiskSide::new();
+        disk_side.disk_info = disk_info;
         // block 2
         let mut buf2: [u8; BLOCK_2_SIZE as usize] = [0; BLOCK_2_SIZE as usize];
-        file.read_exact(&mut buf2)?;
+        let block_2_offset = file.stream_position()?;
+        file.read_exact(&mut buf2).map_err(|e| RomError::from(e).with_format(RomFormat::Fds).with_field(format!("side {} block 2", side)).with_offset(block_2_offset))?;
         // check block code
         if buf2[0] != 2 {
-            return Err(RomError::InvalidRom);
+            return Err(fds_error(ErrorKind::InvalidRom, format!("side {} block 2", side)).with_offset(block_2_offset).with_values(2, buf2[0]));
+        }
+        if qd {
+            Fds::read_block_crc(file, &buf2, format!("side {} block 2", side))?;
         }
+        disk_side.file_amount = buf2[1];
 
+        // keep reading past the declared file amount, anything found there is a hidden file
         let mut last_file_good: bool = true;
         while last_file_good {
-            match Fds::read_disk_files(file) {
+            let declared_left = disk_side.files.len() < disk_side.file_amount as usize;
+            match Fds::read_disk_files(file, side, qd, declared_left, &mut disk_side.diagnostics)? {
                 Some(mut fd) => {
                     fd.disk_number = disk_num;
                     fd.side_number = side_num;
                     fd.actual_disk_number = actual_disk_num;
                     fd.boot_read_file_code = boot_read_file_code;
-                    fv.push(fd)
+                    fd.hidden = disk_side.files.len() >= disk_side.file_amount as usize;
+                    disk_side.files.push(fd)
                 },
                 None => last_file_good = false,
             }
         }
 
-        Ok(())
+        if disk_side.files.len() < disk_side.file_amount as usize {
+            disk_side.diagnostics.push(DiskDiagnostic::MissingFiles { declared: disk_side.file_amount, found: disk_side.files.len() as u32 });
+        }
+
+        Ok(disk_side)
      }
 
-    fn read_disk_files<R: Read>(file: &mut R) -> Option<FileData> {
+    fn read_disk_files<R: Read + Seek>(file: &mut R, side: u8, qd: bool, declared_left: bool, diagnostics: &mut Vec<DiskDiagnostic>) -> Result<Option<FileData>, RomError> {
         // read file header
+        let block_3_offset = file.stream_position()?;
         let mut buf: [u8; BLOCK_3_SIZE as usize] = [0; BLOCK_3_SIZE as usize];
-        match file.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(_) => return None,
+        if file.read_exact(&mut buf).is_err() {
+            return Ok(None);
         }
 
         // disk will be zero filled after last file
-        if buf[0] != 0x03 || buf[16] != 0x04 {
-            return None;
+        if buf[0] == 0x00 {
+            return Ok(None);
+        }
+        // left over data after the declared files is normal, it only ends the file list
+        if buf[0] != 0x03 {
+            if declared_left {
+                diagnostics.push(DiskDiagnostic::UnexpectedBlockCode { offset: block_3_offset, found: buf[0] });
+            }
+            return Ok(None);
+        }
+        if qd {
+            Fds::read_block_crc(file, &buf, format!("side {} block 3", side))?;
         }
 
-        let mut fd = FileData::new();
-        fd.file_number = buf[1];
-        fd.file_id = buf[2];
-        fd.file_name.copy_from_slice(&buf[3..11]);
-        fd.file_address = ((buf[12] as u16) << 8) | buf[11] as u16;
-        fd.file_size = ((buf[14] as u16) << 8) | buf[13] as u16;
-        fd.file_type = match KindOfFile::from_u8(buf[15]) {
-            Some(kof) => kof,
-            None => return None,
-        };
+        let mut fd = FileData::from_header_block(&buf);
+        if let KindOfFile::Other(kind) = fd.file_type {
+            diagnostics.push(DiskDiagnostic::UnknownFileKind { file_number: fd.file_number, kind });
+        }
 
-        fd.file_data = vec![0; (fd.file_size) as usize];
-        match file.read_exact(fd.file_data.as_mut_slice()) {
-            Ok(_) => (),
-            Err(_) => return None,
+        // block code
+        let block_4_offset = file.stream_position()?;
+        let mut code: [u8; 1] = [0];
+        if file.read_exact(&mut code).is_err() || code[0] != 0x04 {
+            diagnostics.push(DiskDiagnostic::MissingFileData { file_number: fd.file_number, offset: block_4_offset, found: code[0] });
+            return Ok(None);
         }
 
-        Some(fd)
+        // file data
+        let mut data = Vec::with_capacity(fd.file_size as usize);
+        file.take(fd.file_size as u64).read_to_end(&mut data)?;
+        if data.len() < fd.file_size as usize {
+            diagnostics.push(DiskDiagnostic::TruncatedFile { file_number: fd.file_number, offset: block_4_offset + 1,
+                expected: fd.file_size, found: data.len() as u16 });
+            data.resize(fd.file_size as usize, 0);
+        }
+        else if qd {
+            let mut block = Vec::with_capacity(1 + data.len());
+            block.push(0x04);
+            block.extend_from_slice(&data);
+            Fds::read_block_crc(file, &block, format!("side {} block 4", side))?;
+        }
+        fd.file_data = data;
+
+        Ok(Some(fd))
     }
 
-}
\ No newline at end of file
+    // .qd images store the crc of each block right after it
+    fn read_block_crc<R: Read + Seek>(file: &mut R, block: &[u8], field: String) -> Result<(), RomError> {
+        let offset = file.stream_position()?;
+        let mut buf: [u8; CRC_SIZE as usize] = [0; CRC_SIZE as usize];
+        file.read_exact(&mut buf).map_err(|e| RomError::from(e).with_format(RomFormat::Fds).with_field(format!("{} crc", field)).with_offset(offset))?;
+
+        let crc = crc16::crc16_fds_from_slice(block);
+        let found = u16::from_le_bytes(buf);
+        if crc != found {
+            return Err(fds_error(ErrorKind::InvalidRom, format!("{} crc", field)).with_offset(offset)
+                .with_values(format!("{:#06X}", crc), format!("{:#06X}", found)));
+        }
+
+        Ok(())
+    }
+
+    /// Serialize to .fds file bytes
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let fds = nes_rom::fds::Fds::new();
+    /// let bytes = fds.to_bytes().unwrap();
+    ///  ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, RomError> {
+        let mut bytes = Vec::with_capacity((HEADER_SIZE + self.sides.len() as u64 * DISK_SIZE) as usize);
+        self.write_to(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Write .fds file, each disk side is zero padded to 65500 bytes.
+    /// The fwNES header is only written if `fwnes_header` is set
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let mut fds = nes_rom::fds::Fds::new();
+    /// // strip the header
+    /// fds.fwnes_header = false;
+    /// let file = std::fs::File::create("disk.fds").unwrap();
+    /// fds.write_to(file).unwrap();
+    ///  ```
+    pub fn write_to<W: Write>(&self, mut file: W) -> Result<(), RomError> {
+        if self.sides.len() > 0xFF {
+            return Err(fds_error(ErrorKind::InvalidRom, "disk sides").with_values("at most 255 sides", self.sides.len()));
+        }
+        if self.fwnes_header {
+            let mut header: [u8; HEADER_SIZE as usize] = [0; HEADER_SIZE as usize];
+            header[0..4].copy_from_slice(&super::FDS_GUARD);
+            header[4] = self.sides.len() as u8;
+            file.write_all(&header)?;
+        }
+
+        for (i, side) in self.sides.iter().enumerate() {
+            file.write_all(&Fds::create_disk_side(side, i, false)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Serialize to .qd file bytes
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let fds = nes_rom::fds::Fds::new();
+    /// let bytes = fds.to_qd().unwrap();
+    ///  ```
+    pub fn to_qd(&self) -> Result<Vec<u8>, RomError> {
+        let mut bytes = Vec::with_capacity((self.sides.len() as u64 * QD_DISK_SIZE) as usize);
+        self.write_qd_to(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Write .qd file, every block is followed by its crc and each disk side is zero padded to 65536 bytes
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let fds = nes_rom::fds::Fds::new();
+    /// let file = std::fs::File::create("disk.qd").unwrap();
+    /// fds.write_qd_to(file).unwrap();
+    ///  ```
+    pub fn write_qd_to<W: Write>(&self, mut file: W) -> Result<(), RomError> {
+        for (i, side) in self.sides.iter().enumerate() {
+            file.write_all(&Fds::create_disk_side(side, i, true)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Files the bios loads when booting from a disk side, in load order.
+    /// These are the declared files with an id up to the boot read file code
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let fds = nes_rom::fds::Fds::new();
+    /// for fd in fds.boot_files(0).unwrap() {
+    ///     println!("{:?} at {:#06X}", fd.file_type, fd.file_address);
+    /// }
+    ///  ```
+    pub fn boot_files(&self, side: usize) -> Result<Vec<&FileData>, RomError> {
+        let disk_side = self.sides.get(side).ok_or_else(|| fds_error(ErrorKind::InvalidRom, "disk sides")
+            .with_values(format!("side below {}", self.sides.len()), side))?;
+
+        let boot_read_file_code = disk_side.disk_info.boot_read_file_code;
+        Ok(disk_side.files.iter()
+            .take(disk_side.file_amount as usize)
+            .filter(|fd| fd.file_id <= boot_read_file_code)
+            .collect())
+    }
+
+    /// Memory contents after the bios loaded the boot files of a disk side
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let fds = nes_rom::fds::Fds::new();
+    /// let image = fds.boot_image(0).unwrap();
+    /// // reset vector
+    /// let reset = u16::from_le_bytes([image.prg_ram[0x7FFC], image.prg_ram[0x7FFD]]);
+    ///  ```
+    pub fn boot_image(&self, side: usize) -> Result<BootImage, RomError> {
+        let mut image = BootImage::new();
+        for fd in self.boot_files(side)? {
+            image.load_file(fd);
+        }
+
+        Ok(image)
+    }
+
+    /// Gap accurate byte stream of a disk side as stored on a real disk.
+    /// Every block is preceded by a zero filled gap and the 0x80 start mark and followed by its crc
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let fds = nes_rom::fds::Fds::new();
+    /// let raw = fds.to_raw_side(0).unwrap();
+    ///  ```
+    pub fn to_raw_side(&self, side: usize) -> Result<Vec<u8>, RomError> {
+        let disk_side = self.sides.get(side).ok_or_else(|| fds_error(ErrorKind::InvalidRom, "disk sides")
+            .with_values(format!("side below {}", self.sides.len()), side))?;
+
+        let mut buf = Vec::new();
+        for (i, block) in Fds::disk_side_blocks(disk_side).iter().enumerate() {
+            let gap = if i == 0 { RAW_LEADING_GAP } else { RAW_BLOCK_GAP };
+            buf.resize(buf.len() + gap, 0);
+            buf.push(RAW_START_MARK);
+            Fds::write_block(&mut buf, block, true);
+        }
+        buf.resize(buf.len() + RAW_BLOCK_GAP, 0);
+
+        Ok(buf)
+    }
+
+    /// Parse gap accurate disk side dumps as produced by `to_raw_side`, one dump per side.
+    /// The block crcs are verified
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// let side_a = std::fs::read("side_a.bin").unwrap();
+    /// let side_b = std::fs::read("side_b.bin").unwrap();
+    /// let fds = nes_rom::fds::Fds::from_raw_sides(&[side_a, side_b]);
+    ///  ```
+    pub fn from_raw_sides<S: AsRef<[u8]>>(sides: &[S]) -> Result<Fds, RomError> {
+        if sides.len() > 0xFF {
+            return Err(fds_error(ErrorKind::InvalidRom, "disk sides").with_values("at most 255 sides", sides.len()));
+        }
+
+        let mut fds = Fds::new();
+        for (i, raw) in sides.iter().enumerate() {
+            let data = Fds::strip_raw_side(raw.as_ref(), i)?;
+            let mut cursor = io::Cursor::new(data);
+            fds.sides.push(Fds::read_disk_side(&mut cursor, (i / 2) as u8, i as u8, 0, false)?);
+        }
+
+        Ok(fds)
+    }
+
+    // removes gaps, start marks and crcs, leaving the blocks in .fds layout
+    fn strip_raw_side(raw: &[u8], side: usize) -> Result<Vec<u8>, RomError> {
+        let mut data = Vec::with_capacity(DISK_SIZE as usize);
+        let mut pos = 0;
+        let mut file_size: Option<usize> = None;
+        loop {
+            // gap
+            while pos < raw.len() && raw[pos] == 0 {
+                pos += 1;
+            }
+            if pos >= raw.len() {
+                break;
+            }
+            if raw[pos] != RAW_START_MARK {
+                // anything but a start mark after the file list is left over data
+                if data.len() >= (BLOCK_1_SIZE + BLOCK_2_SIZE) as usize {
+                    break;
+                }
+                return Err(fds_error(ErrorKind::InvalidRom, format!("side {} start mark", side)).with_offset(pos as u64)
+                    .with_values(format!("{:#04X}", RAW_START_MARK), format!("{:#04X}", raw[pos])));
+            }
+            pos += 1;
+
+            let block_code = raw.get(pos).copied().unwrap_or(0);
+            let block_size = match block_code {
+                0x01 => BLOCK_1_SIZE as usize,
+                0x02 => BLOCK_2_SIZE as usize,
+                0x03 => BLOCK_3_SIZE as usize,
+                0x04 => match file_size.take() {
+                    Some(size) => 1 + size,
+                    None => return Err(fds_error(ErrorKind::InvalidRom, format!("side {} block 4", side)).with_offset(pos as u64)
+                        .with_values("block 3 in front", "none")),
+                },
+                _ => return Err(fds_error(ErrorKind::InvalidRom, format!("side {} block code", side)).with_offset(pos as u64)
+                    .with_values("1 to 4", block_code)),
+            };
+
+            let field = format!("side {} block {}", side, block_code);
+            if pos + block_size + CRC_SIZE as usize > raw.len() {
+                return Err(fds_error(ErrorKind::InvalidRom, field).with_offset(pos as u64)
+                    .with_values(format!("{} bytes", block_size + CRC_SIZE as usize), format!("{} bytes", raw.len() - pos)));
+            }
+
+            let block = &raw[pos..pos + block_size];
+            let crc = crc16::crc16_fds_from_slice(block);
+            let found = u16::from_le_bytes([raw[pos + block_size], raw[pos + block_size + 1]]);
+            if crc != found {
+                return Err(fds_error(ErrorKind::InvalidRom, format!("{} crc", field)).with_offset((pos + block_size) as u64)
+                    .with_values(format!("{:#06X}", crc), format!("{:#06X}", found)));
+            }
+
+            if block_code == 0x03 {
+                file_size = Some(u16::from_le_bytes([block[13], block[14]]) as usize);
+            }
+            data.extend_from_slice(block);
+            pos += block_size + CRC_SIZE as usize;
+        }
+
+        Ok(data)
+    }
+
+    /// Changes needed to turn this disk into `other`, only the sides present in both are compared
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let pristine = nes_rom::fds::Fds::new();
+    /// # let saved = nes_rom::fds::Fds::new();
+    /// let patch = pristine.diff(&saved);
+    /// for change in &patch.changes {
+    ///     println!("{:?}", change);
+    /// }
+    ///  ```
+    pub fn diff(&self, other: &Fds) -> FdsPatch {
+        let mut patch = FdsPatch::new();
+        for (i, (old, new)) in self.sides.iter().zip(other.sides.iter()).enumerate() {
+            let side = i as u8;
+            if old.file_amount != new.file_amount {
+                patch.changes.push(PatchChange::FileAmount { side, file_amount: new.file_amount });
+            }
+
+            for (index, fd) in new.files.iter().enumerate() {
+                let changed = match old.files.get(index) {
+                    Some(old_fd) => old_fd.header_block() != fd.header_block() || old_fd.file_data != fd.file_data,
+                    None => true,
+                };
+                if changed {
+                    patch.changes.push(PatchChange::File { side, index: index as u16, file: fd.clone() });
+                }
+            }
+
+            if new.files.len() < old.files.len() {
+                patch.changes.push(PatchChange::Truncate { side, index: new.files.len() as u16 });
+            }
+        }
+
+        patch
+    }
+
+    /// Apply the changes of a patch created by `diff`
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let mut fds = nes_rom::fds::Fds::new();
+    /// # let arg = std::fs::File::open("disk.fdsp").unwrap();
+    /// let patch = nes_rom::fds::FdsPatch::from_patch(arg).unwrap();
+    /// fds.apply(&patch).unwrap();
+    ///  ```
+    pub fn apply(&mut self, patch: &FdsPatch) -> Result<(), RomError> {
+        let sides_amount = self.sides.len();
+        for change in &patch.changes {
+            let side = match *change {
+                PatchChange::FileAmount { side, .. } | PatchChange::File { side, .. } | PatchChange::Truncate { side, .. } => side,
+            };
+            let disk_side = self.sides.get_mut(side as usize).ok_or_else(|| fds_error(ErrorKind::InvalidConversion, "patch side")
+                .with_values(format!("side below {}", sides_amount), side))?;
+
+            match *change {
+                PatchChange::FileAmount { file_amount, .. } => disk_side.file_amount = file_amount,
+                PatchChange::File { index, ref file, .. } => {
+                    let index = index as usize;
+                    if index > disk_side.files.len() {
+                        return Err(fds_error(ErrorKind::InvalidConversion, format!("patch side {} file", side))
+                            .with_values(format!("index up to {}", disk_side.files.len()), index));
+                    }
+
+                    let mut fd = file.clone();
+                    fd.disk_number = disk_side.disk_info.disk_number;
+                    fd.side_number = disk_side.disk_info.side_number;
+                    fd.actual_disk_number = side / 2;
+                    fd.boot_read_file_code = disk_side.disk_info.boot_read_file_code;
+                    fd.file_size = fd.file_data.len() as u16;
+                    if index == disk_side.files.len() {
+                        disk_side.files.push(fd);
+                    }
+                    else {
+                        disk_side.files[index] = fd;
+                    }
+                },
+                PatchChange::Truncate { index, .. } => disk_side.files.truncate(index as usize),
+            }
+
+            let file_amount = disk_side.file_amount as usize;
+            for (i, fd) in disk_side.files.iter_mut().enumerate() {
+                fd.hidden = i >= file_amount;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_disk_side(side: &DiskSide, index: usize, qd: bool) -> Result<Vec<u8>, RomError> {
+        let side_size = if qd { QD_DISK_SIZE } else { DISK_SIZE };
+        let mut buf = Vec::with_capacity(side_size as usize);
+        for block in Fds::disk_side_blocks(side) {
+            Fds::write_block(&mut buf, &block, qd);
+        }
+
+        if buf.len() as u64 > side_size {
+            return Err(fds_error(ErrorKind::InvalidRom, format!("side {} files", index))
+                .with_values(format!("at most {} bytes", side_size), buf.len()));
+        }
+
+        // disk is zero filled after last file
+        buf.resize(side_size as usize, 0);
+        Ok(buf)
+    }
+
+    fn disk_side_blocks(side: &DiskSide) -> Vec<Vec<u8>> {
+        let mut blocks = Vec::with_capacity(2 + side.files.len() * 2);
+
+        // block 1
+        blocks.push(side.disk_info.to_block().to_vec());
+
+        // block 2, written as declared, files past the declared amount are hidden files
+        // and copy protected disks may declare more files than are present
+        blocks.push(vec![0x02, side.file_amount]);
+
+        // block 3 and 4
+        for fd in &side.files {
+            blocks.push(fd.header_block().to_vec());
+
+            let mut block4 = Vec::with_capacity(1 + fd.file_data.len());
+            block4.push(0x04);
+            block4.extend_from_slice(&fd.file_data);
+            blocks.push(block4);
+        }
+
+        blocks
+    }
+
+    fn write_block(buf: &mut Vec<u8>, block: &[u8], crc: bool) {
+        buf.extend_from_slice(block);
+        if crc {
+            buf.extend_from_slice(&crc16::crc16_fds_from_slice(block).to_le_bytes());
+        }
+    }
+}
+
+impl FdsPatch {
+    pub fn new() -> FdsPatch {
+        FdsPatch {
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Load and parse a patch written by `write_to`
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let arg = std::fs::File::open("disk.fdsp").unwrap();
+    /// let patch = nes_rom::fds::FdsPatch::from_patch(arg);
+    ///  ```
+    pub fn from_patch<R: Read>(mut file: R) -> Result<FdsPatch, RomError> {
+        let mut header: [u8; 9] = [0; 9];
+        file.read_exact(&mut header).map_err(|e| RomError::from(e).with_format(RomFormat::Fds).with_field("patch header").with_offset(0))?;
+        if header[0..4] != PATCH_GUARD {
+            return Err(fds_error(ErrorKind::InvalidFormat, "patch magic").with_offset(0)
+                .with_values(format!("{:02X?}", PATCH_GUARD), format!("{:02X?}", &header[0..4])));
+        }
+        if header[4] != PATCH_VERSION {
+            return Err(fds_error(ErrorKind::InvalidFormat, "patch version").with_offset(4).with_values(PATCH_VERSION, header[4]));
+        }
+
+        let amount = u32::from_le_bytes([header[5], header[6], header[7], header[8]]);
+        let mut patch = FdsPatch::new();
+        for i in 0..amount {
+            let field = format!("patch change {}", i);
+            let mut buf: [u8; 2] = [0; 2];
+            file.read_exact(&mut buf).map_err(|e| RomError::from(e).with_format(RomFormat::Fds).with_field(field.clone()))?;
+            let side = buf[1];
+
+            let change = match buf[0] {
+                0 => {
+                    let mut amount: [u8; 1] = [0];
+                    file.read_exact(&mut amount).map_err(|e| RomError::from(e).with_format(RomFormat::Fds).with_field(field.clone()))?;
+                    PatchChange::FileAmount { side, file_amount: amount[0] }
+                },
+                1 => {
+                    let mut index: [u8; 2] = [0; 2];
+                    let mut block3: [u8; BLOCK_3_SIZE as usize] = [0; BLOCK_3_SIZE as usize];
+                    file.read_exact(&mut index).and_then(|_| file.read_exact(&mut block3))
+                        .map_err(|e| RomError::from(e).with_format(RomFormat::Fds).with_field(field.clone()))?;
+
+                    let mut fd = FileData::from_header_block(&block3);
+                    fd.file_data = vec![0; fd.file_size as usize];
+                    file.read_exact(&mut fd.file_data).map_err(|e| RomError::from(e).with_format(RomFormat::Fds).with_field(field.clone()))?;
+                    PatchChange::File { side, index: u16::from_le_bytes(index), file: fd }
+                },
+                2 => {
+                    let mut index: [u8; 2] = [0; 2];
+                    file.read_exact(&mut index).map_err(|e| RomError::from(e).with_format(RomFormat::Fds).with_field(field.clone()))?;
+                    PatchChange::Truncate { side, index: u16::from_le_bytes(index) }
+                },
+                code => return Err(fds_error(ErrorKind::InvalidFormat, field).with_values("change type 0 to 2", code)),
+            };
+            patch.changes.push(change);
+        }
+
+        Ok(patch)
+    }
+
+    /// Serialize to patch file bytes
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let patch = nes_rom::fds::FdsPatch::new();
+    /// let bytes = patch.to_bytes();
+    ///  ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // writing into a vec can't fail
+        let _ = self.write_to(&mut bytes);
+        bytes
+    }
+
+    /// Write patch file
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let patch = nes_rom::fds::FdsPatch::new();
+    /// let file = std::fs::File::create("disk.fdsp").unwrap();
+    /// patch.write_to(file).unwrap();
+    ///  ```
+    pub fn write_to<W: Write>(&self, mut file: W) -> Result<(), RomError> {
+        file.write_all(&PATCH_GUARD)?;
+        file.write_all(&[PATCH_VERSION])?;
+        file.write_all(&(self.changes.len() as u32).to_le_bytes())?;
+
+        for change in &self.changes {
+            match *change {
+                PatchChange::FileAmount { side, file_amount } => {
+                    file.write_all(&[0, side, file_amount])?;
+                },
+                PatchChange::File { side, index, file: ref fd } => {
+                    file.write_all(&[1, side])?;
+                    file.write_all(&index.to_le_bytes())?;
+                    file.write_all(&fd.header_block())?;
+                    file.write_all(&fd.file_data)?;
+                },
+                PatchChange::Truncate { side, index } => {
+                    file.write_all(&[2, side])?;
+                    file.write_all(&index.to_le_bytes())?;
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Fds {
+    fn default() -> Self {
+        Fds::new()
+    }
+}
+
+// disk images have no rom chips, the program is loaded into ram adapter memory by the bios
+impl RomInfo for Fds {
+    fn format(&self) -> RomFormat {
+        RomFormat::Fds
+    }
+
+    fn prg(&self) -> &[u8] {
+        &[]
+    }
+
+    fn chr(&self) -> &[u8] {
+        &[]
+    }
+
+    fn prg_crc(&self) -> u32 {
+        0
+    }
+
+    fn chr_crc(&self) -> u32 {
+        0
+    }
+
+    // fds images use nes 2.0 mapper 20
+    fn mapper(&self) -> Option<u32> {
+        Some(20)
+    }
+
+    fn submapper(&self) -> Option<u8> {
+        None
+    }
+
+    fn board(&self) -> Option<&str> {
+        None
+    }
+
+    // mirroring is set by the ram adapter $4025 register
+    fn mirroring(&self) -> Mirroring {
+        Mirroring::MapperControlled
+    }
+
+    fn region(&self) -> Region {
+        Region::NTSC
+    }
+
+    // disks are rewritable, there is no battery backed ram
+    fn has_battery(&self) -> bool {
+        false
+    }
+}
+
+/// Decode text stored on disk, ascii and half width katakana.
+/// Trailing zero padding is removed and other bytes are escaped as \xNN
+/// 
+/// # Examples
+/// 
+///  ```
+/// assert_eq!(nes_rom::fds::decode_text(b"SM\xB1\x01\0\0"), "SMｱ\\x01");
+///  ```
+pub fn decode_text(bytes: &[u8]) -> String {
+    let len = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    let mut text = String::with_capacity(len);
+    for &b in &bytes[..len] {
+        match b {
+            0x20..=0x7E => text.push(b as char),
+            // jis x 0201 katakana, mapped to the unicode half width forms
+            0xA1..=0xDF => text.push(char::from_u32(0xFF61 + (b - 0xA1) as u32).unwrap_or(char::REPLACEMENT_CHARACTER)),
+            _ => text.push_str(&format!("\\x{:02X}", b)),
+        }
+    }
+
+    text
+}
+
+/// Licensee name of a block 1 manufacturer code, the codes are shared with the game boy's old licensee codes
+/// 
+/// # Examples
+/// 
+///  ```
+/// assert_eq!(nes_rom::fds::licensee_name(0x01), Some("Nintendo"));
+///  ```
+pub fn licensee_name(code: u8) -> Option<&'static str> {
+    let name = match code {
+        0x00 => "unlicensed",
+        0x01 => "Nintendo",
+        0x08 => "Capcom",
+        0x0A => "Jaleco",
+        0x18 => "Hudson Soft",
+        0x49 => "Irem",
+        0x4A => "Gakken",
+        0x8B => "BulletProof Software",
+        0x99 => "Pack-In-Video",
+        0x9B => "Tecmo",
+        0x9C => "Imagineer",
+        0xA2 => "Scorpion Soft",
+        0xA4 => "Konami",
+        0xA6 => "Kawada",
+        0xA7 => "Takara",
+        0xA8 => "Royal Industries",
+        0xAC => "Toei Animation",
+        0xAF => "Namco",
+        0xB1 => "ASCII Corporation",
+        0xB2 => "Bandai",
+        0xB3 => "Soft Pro",
+        0xB6 => "HAL Laboratory",
+        0xBB => "Sunsoft",
+        0xBC => "Toshiba EMI",
+        0xC0 => "Taito",
+        0xC1 => "Sunsoft / Ask",
+        0xC2 => "Kemco",
+        0xC3 => "Square",
+        0xC4 => "Tokuma Shoten",
+        0xC5 => "Data East",
+        0xC6 => "Tonkin House",
+        0xC7 => "East Cube",
+        0xCA => "Konami / Ultra / Palcom",
+        0xCB => "NTVIC / VAP",
+        0xCC => "Use",
+        0xCE => "Pony Canyon / FCI",
+        0xD1 => "Sofel",
+        0xD2 => "Bothtec",
+        0xDB => "Hiro",
+        0xE7 => "Athena",
+        0xEB => "Atlus",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+fn bcd_to_u8(bcd: u8) -> Option<u8> {
+    if (bcd & 0x0F) > 9 || (bcd >> 4) > 9 {
+        return None;
+    }
+
+    Some((bcd >> 4) * 10 + (bcd & 0x0F))
+}
+
+fn fds_error<S: Into<String>>(kind: ErrorKind, field: S) -> RomError {
+    RomError::new(kind).with_format(RomFormat::Fds).with_field(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn example_rom(name: &str) -> Vec<u8> {
+        fs::read(format!("{}/example_roms/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap()
+    }
+
+    #[test]
+    fn missing_declared_files_are_written_back() {
+        let mut rom = example_rom("Donkey Kong (1981)(Nintendo).fds");
+        // block 2 follows the fwNES header and block 1
+        let file_amount = (HEADER_SIZE + BLOCK_1_SIZE + 1) as usize;
+        assert_eq!(rom[file_amount], 3);
+        rom[file_amount] = 5;
+
+        let fds = Fds::from_rom(io::Cursor::new(&rom)).unwrap();
+        assert_eq!(fds.sides[0].diagnostics, vec![DiskDiagnostic::MissingFiles { declared: 5, found: 3 }]);
+        assert!(fds.to_bytes().unwrap() == rom);
+        assert!(fds.to_qd().is_ok());
+        assert!(fds.to_raw_side(0).is_ok());
+    }
+
+    #[test]
+    fn headerless_trailing_padding_is_not_a_side() {
+        let rom = example_rom("Donkey Kong (1981)(Nintendo).fds");
+        let fds = Fds::from_rom(io::Cursor::new(&rom)).unwrap();
+        let mut headerless = rom[HEADER_SIZE as usize..].to_vec();
+
+        // padding after the last side
+        headerless.resize(headerless.len() + 256, 0);
+        let padded = Fds::from_rom(io::Cursor::new(&headerless)).unwrap();
+        assert_eq!(padded.sides, fds.sides);
+
+        // last side cut short after its files
+        let mut cut = rom[HEADER_SIZE as usize..].to_vec();
+        cut.truncate(cut.len() - 1000);
+        let cut = Fds::from_rom(io::Cursor::new(&cut)).unwrap();
+        assert_eq!(cut.sides, fds.sides);
+    }
+
+    #[test]
+    fn left_over_data_after_declared_files_is_not_damage() {
+        let rom = example_rom("Legend of Zelda 2, The - Link no Bouken (1987)(Nintendo)[a2].fds");
+        let fds = Fds::from_rom(io::Cursor::new(&rom)).unwrap();
+        for side in &fds.sides {
+            assert_eq!(side.files.len(), side.file_amount as usize);
+            assert!(side.diagnostics.is_empty(), "{:?}", side.diagnostics);
+        }
+
+        // a block code where a declared file should start is reported
+        let mut rom = example_rom("Donkey Kong (1981)(Nintendo).fds");
+        let block_3 = (HEADER_SIZE + BLOCK_1_SIZE + BLOCK_2_SIZE) as usize;
+        rom[block_3] = 0x05;
+        let fds = Fds::from_rom(io::Cursor::new(&rom)).unwrap();
+        assert_eq!(fds.sides[0].diagnostics, vec![
+            DiskDiagnostic::UnexpectedBlockCode { offset: block_3 as u64, found: 0x05 },
+            DiskDiagnostic::MissingFiles { declared: 3, found: 0 },
+        ]);
+    }
+
+    // crcs of the first blocks of side A, computed with the bit serial algorithm of the fds bios
+    const DONKEY_KONG_BLOCK_1_CRC: u16 = 0x7B7C;
+    const DONKEY_KONG_BLOCK_2_CRC: u16 = 0x0DC7;
+    const DONKEY_KONG_BLOCK_3_CRC: u16 = 0x7BA0;
+    const DONKEY_KONG_BLOCK_4_CRC: u16 = 0xE353;
+
+    #[test]
+    fn qd_blocks_are_followed_by_crcs() {
+        let rom = example_rom("Donkey Kong (1981)(Nintendo).fds");
+        let fds = Fds::from_rom(io::Cursor::new(&rom)).unwrap();
+        let side = &rom[HEADER_SIZE as usize..];
+
+        let qd = fds.to_qd().unwrap();
+        assert_eq!(qd.len() as u64, fds.sides.len() as u64 * QD_DISK_SIZE);
+        // block 1, block 2, block 3 and block 4 of the first file
+        let file_size = u16::from_le_bytes([side[71], side[72]]) as usize;
+        let mut pos = 0;
+        let mut src = 0;
+        for (len, crc) in [(56, DONKEY_KONG_BLOCK_1_CRC), (2, DONKEY_KONG_BLOCK_2_CRC), (16, DONKEY_KONG_BLOCK_3_CRC), (1 + file_size, DONKEY_KONG_BLOCK_4_CRC)] {
+            assert_eq!(qd[pos..pos + len], side[src..src + len]);
+            assert_eq!(qd[pos + len..pos + len + 2], crc.to_le_bytes());
+            pos += len + 2;
+            src += len;
+        }
+
+        let parsed = Fds::from_rom(io::Cursor::new(&qd)).unwrap();
+        assert_eq!(parsed.sides, fds.sides);
+
+        // a damaged crc is reported
+        let mut damaged = qd.clone();
+        damaged[57] ^= 0xFF;
+        let e = Fds::from_rom(io::Cursor::new(&damaged)).unwrap_err();
+        assert_eq!(e.field(), Some("side 0 block 1 crc"));
+    }
+
+    #[test]
+    fn raw_side_layout() {
+        let rom = example_rom("Donkey Kong (1981)(Nintendo).fds");
+        let fds = Fds::from_rom(io::Cursor::new(&rom)).unwrap();
+        let side = &rom[HEADER_SIZE as usize..];
+
+        let raw = fds.to_raw_side(0).unwrap();
+        assert!(raw[..RAW_LEADING_GAP].iter().all(|b| *b == 0));
+        let mut pos = RAW_LEADING_GAP;
+        assert_eq!(raw[pos], RAW_START_MARK);
+        assert_eq!(raw[pos + 1..pos + 57], side[0..56]);
+        assert_eq!(raw[pos + 57..pos + 59], DONKEY_KONG_BLOCK_1_CRC.to_le_bytes());
+        pos += 59;
+
+        assert!(raw[pos..pos + RAW_BLOCK_GAP].iter().all(|b| *b == 0));
+        pos += RAW_BLOCK_GAP;
+        assert_eq!(raw[pos..pos + 5], [RAW_START_MARK, 0x02, side[57], 0xC7, 0x0D]);
+
+        let sides: Vec<Vec<u8>> = (0..fds.sides.len()).map(|i| fds.to_raw_side(i).unwrap()).collect();
+        let parsed = Fds::from_raw_sides(&sides).unwrap();
+        assert_eq!(parsed.sides, fds.sides);
+    }
+
+    const ZELDA_2: &str = "Legend of Zelda 2, The - Link no Bouken (1987)(Nintendo)[a2].fds";
+
+    #[test]
+    fn saved_file_patch() {
+        let pristine = Fds::from_rom(io::Cursor::new(example_rom(ZELDA_2))).unwrap();
+        let mut saved = pristine.clone();
+        // the game saves into the last file of side A
+        let index = saved.sides[0].files.len() - 1;
+        saved.sides[0].files[index].file_data[0] ^= 0xFF;
+
+        let patch = pristine.diff(&saved);
+        assert_eq!(patch.changes, vec![PatchChange::File { side: 0, index: index as u16, file: saved.sides[0].files[index].clone() }]);
+        assert!(pristine.diff(&pristine).is_empty());
+
+        let mut patched = pristine.clone();
+        patched.apply(&patch).unwrap();
+        assert_eq!(patched, saved);
+        assert!(patched.to_bytes().unwrap() == saved.to_bytes().unwrap());
+
+        // the patch file only keeps block 3 and 4, apply fills in the rest from the disk side
+        let written = FdsPatch::from_patch(io::Cursor::new(patch.to_bytes())).unwrap();
+        assert!(written.to_bytes() == patch.to_bytes());
+        let mut patched = pristine.clone();
+        patched.apply(&written).unwrap();
+        assert_eq!(patched, saved);
+    }
+
+    #[test]
+    fn file_amount_and_truncate_patch() {
+        let pristine = Fds::from_rom(io::Cursor::new(example_rom(ZELDA_2))).unwrap();
+        let mut changed = pristine.clone();
+
+        // last file of side A removed
+        let side_a = &mut changed.sides[0];
+        side_a.files.pop();
+        side_a.file_amount -= 1;
+
+        // file appended to side B
+        let side_b = &mut changed.sides[1];
+        let mut fd = side_b.files[0].clone();
+        fd.file_number = side_b.files.len() as u8;
+        fd.file_data = vec![0x55; 0x100];
+        fd.file_size = 0x100;
+        side_b.files.push(fd.clone());
+        side_b.file_amount += 1;
+
+        let patch = pristine.diff(&changed);
+        let files_a = changed.sides[0].files.len() as u16;
+        let files_b = changed.sides[1].files.len() as u16;
+        assert_eq!(patch.changes, vec![
+            PatchChange::FileAmount { side: 0, file_amount: files_a as u8 },
+            PatchChange::Truncate { side: 0, index: files_a },
+            PatchChange::FileAmount { side: 1, file_amount: files_b as u8 },
+            PatchChange::File { side: 1, index: files_b - 1, file: fd },
+        ]);
+
+        let written = FdsPatch::from_patch(io::Cursor::new(patch.to_bytes())).unwrap();
+        assert!(written.to_bytes() == patch.to_bytes());
+
+        let mut patched = pristine.clone();
+        patched.apply(&written).unwrap();
+        assert_eq!(patched, changed);
+
+        // patches only apply to sides that exist
+        let mut one_side = pristine.clone();
+        one_side.sides.truncate(1);
+        let e = one_side.apply(&patch).unwrap_err();
+        assert_eq!(e.field(), Some("patch side"));
+    }
+
+    #[test]
+    fn invalid_patch_header() {
+        let mut patch = FdsPatch::new();
+        patch.changes.push(PatchChange::Truncate { side: 0, index: 1 });
+        let bytes = patch.to_bytes();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        let e = FdsPatch::from_patch(io::Cursor::new(bad_magic)).unwrap_err();
+        assert_eq!((e.kind(), e.field(), e.offset()), (ErrorKind::InvalidFormat, Some("patch magic"), Some(0)));
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = PATCH_VERSION + 1;
+        let e = FdsPatch::from_patch(io::Cursor::new(bad_version)).unwrap_err();
+        assert_eq!((e.kind(), e.field(), e.offset()), (ErrorKind::InvalidFormat, Some("patch version"), Some(4)));
+
+        let mut bad_change = bytes.clone();
+        bad_change[9] = 3;
+        let e = FdsPatch::from_patch(io::Cursor::new(bad_change)).unwrap_err();
+        assert_eq!((e.kind(), e.field()), (ErrorKind::InvalidFormat, Some("patch change 0")));
+
+        // change cut short
+        let e = FdsPatch::from_patch(io::Cursor::new(&bytes[..bytes.len() - 1])).unwrap_err();
+        assert_eq!(e.field(), Some("patch change 0"));
+    }
+
+    #[test]
+    fn zelda_2_boot_files() {
+        let mut fds = Fds::from_rom(io::Cursor::new(example_rom(ZELDA_2))).unwrap();
+        // hidden file past the declared files with a boot file id
+        let side = &mut fds.sides[0];
+        let mut hidden = side.files[0].clone();
+        hidden.file_number = side.files.len() as u8;
+        hidden.hidden = true;
+        side.files.push(hidden);
+
+        let side = &fds.sides[0];
+        let code = side.disk_info.boot_read_file_code;
+        let boot_files = fds.boot_files(0).unwrap();
+        let expected: Vec<&FileData> = side.files[..side.file_amount as usize].iter().filter(|fd| fd.file_id <= code).collect();
+        assert!(!boot_files.is_empty());
+        assert_eq!(boot_files, expected);
+        assert!(boot_files.iter().all(|fd| !fd.hidden));
+        assert!(fds.boot_files(fds.sides.len()).is_err());
+
+        // the reset vector comes from the last boot file covering $DFFC
+        let vector_file = boot_files.iter()
+            .filter(|fd| fd.file_type == KindOfFile::PrgRam)
+            .rfind(|fd| (fd.file_address as usize..fd.file_address as usize + fd.file_data.len()).contains(&0xDFFC))
+            .unwrap();
+        let pos = 0xDFFC - vector_file.file_address as usize;
+        let image = fds.boot_image(0).unwrap();
+        assert_eq!(image.prg_ram[0x7FFC..0x7FFE], vector_file.file_data[pos..pos + 2]);
+    }
+
+    #[test]
+    fn boot_image_drops_bytes_outside_ram() {
+        let mut image = BootImage::new();
+        let mut fd = FileData::new();
+        // console ram
+        fd.file_address = 0x0200;
+        fd.file_data = vec![0xAA; 0x10];
+        image.load_file(&fd);
+        assert_eq!(image, BootImage::new());
+
+        // crosses the end of prg ram at $DFFF
+        fd.file_address = 0xDFF8;
+        image.load_file(&fd);
+        assert_eq!(image.prg_ram.len(), PRG_RAM_SIZE);
+        assert!(image.prg_ram[..0x7FF8].iter().all(|b| *b == 0));
+        assert!(image.prg_ram[0x7FF8..].iter().all(|b| *b == 0xAA));
+
+        // ppu files below $3000 land in chr ram and the nametables
+        fd.file_type = KindOfFile::ChrRam;
+        fd.file_address = 0x1FF8;
+        image.load_file(&fd);
+        assert!(image.chr_ram[0x1FF8..].iter().all(|b| *b == 0xAA));
+        assert!(image.nametables[..8].iter().all(|b| *b == 0xAA));
+        assert!(image.nametables[8..].iter().all(|b| *b == 0));
+    }
+}
@@ -2,7 +2,56 @@ use std::io;
 use std::io::prelude::*;
 use num_derive::FromPrimitive;    
 use num_traits::FromPrimitive;
-use super::{RomError,crc32};
+use super::{RomError,ErrorKind,RomFormat,RomInfo,Mirroring,Region,ParseOptions,crc32,unif};
+
+const PC10_INST_ROM_SIZE: usize = 8 * 1024;
+// lenient parsing zero pads truncated rom data up to this size, roughly the largest simple form nes 2.0 size
+const MAX_PADDED_ROM_SIZE: u32 = 64 * 1024 * 1024;
+
+// 2C03 palette, one octal digit per red, green and blue level
+const RGB_PPU_PALETTE: [u16; 64] = [
+    0o333, 0o014, 0o006, 0o326, 0o403, 0o503, 0o510, 0o420, 0o320, 0o120, 0o031, 0o040, 0o022, 0o000, 0o000, 0o000,
+    0o555, 0o036, 0o027, 0o407, 0o507, 0o704, 0o700, 0o630, 0o430, 0o140, 0o040, 0o053, 0o044, 0o000, 0o000, 0o000,
+    0o777, 0o357, 0o447, 0o637, 0o707, 0o737, 0o740, 0o750, 0o660, 0o360, 0o070, 0o276, 0o077, 0o000, 0o000, 0o000,
+    0o777, 0o567, 0o657, 0o757, 0o747, 0o755, 0o764, 0o772, 0o773, 0o572, 0o473, 0o276, 0o467, 0o000, 0o000, 0o000,
+];
+
+// colors of the RP2C04 ppus, the 2C03 palette with extra colors in place of its duplicate blacks
+const RP2C04_PPU_COLORS: [u16; 64] = [
+    0o333, 0o014, 0o006, 0o326, 0o403, 0o503, 0o510, 0o420, 0o320, 0o120, 0o031, 0o040, 0o022, 0o111, 0o003, 0o020,
+    0o555, 0o036, 0o027, 0o407, 0o507, 0o704, 0o700, 0o630, 0o430, 0o140, 0o040, 0o053, 0o044, 0o222, 0o200, 0o310,
+    0o777, 0o357, 0o447, 0o637, 0o707, 0o737, 0o740, 0o750, 0o660, 0o360, 0o070, 0o276, 0o077, 0o444, 0o000, 0o000,
+    0o777, 0o567, 0o657, 0o757, 0o747, 0o755, 0o764, 0o770, 0o773, 0o572, 0o473, 0o276, 0o467, 0o666, 0o653, 0o760,
+];
+
+// each RP2C04 scrambles the order of the colors, palette index to color index
+const RP2C04_0001_ORDER: [u8; 64] = [
+    0x35, 0x23, 0x16, 0x22, 0x1C, 0x09, 0x1D, 0x15, 0x20, 0x00, 0x27, 0x05, 0x04, 0x28, 0x08, 0x20,
+    0x21, 0x3E, 0x1F, 0x29, 0x3C, 0x32, 0x36, 0x12, 0x3F, 0x2B, 0x2E, 0x1E, 0x3D, 0x2D, 0x24, 0x01,
+    0x0E, 0x31, 0x33, 0x2A, 0x2C, 0x0C, 0x1B, 0x14, 0x2E, 0x07, 0x34, 0x06, 0x13, 0x02, 0x26, 0x2E,
+    0x2E, 0x19, 0x10, 0x0A, 0x39, 0x03, 0x37, 0x17, 0x0F, 0x11, 0x0B, 0x0D, 0x38, 0x25, 0x18, 0x3A,
+];
+
+const RP2C04_0002_ORDER: [u8; 64] = [
+    0x2E, 0x27, 0x18, 0x39, 0x3A, 0x25, 0x1C, 0x31, 0x16, 0x13, 0x38, 0x34, 0x20, 0x23, 0x3C, 0x0B,
+    0x0F, 0x21, 0x06, 0x3D, 0x1B, 0x29, 0x1E, 0x22, 0x1D, 0x24, 0x0E, 0x2B, 0x32, 0x08, 0x2E, 0x03,
+    0x04, 0x36, 0x26, 0x33, 0x11, 0x1F, 0x10, 0x02, 0x14, 0x3F, 0x00, 0x09, 0x12, 0x2E, 0x28, 0x20,
+    0x3E, 0x0D, 0x2A, 0x17, 0x0C, 0x01, 0x15, 0x19, 0x2E, 0x2C, 0x07, 0x37, 0x35, 0x05, 0x0A, 0x2D,
+];
+
+const RP2C04_0003_ORDER: [u8; 64] = [
+    0x14, 0x25, 0x3A, 0x10, 0x0B, 0x20, 0x31, 0x09, 0x01, 0x2E, 0x36, 0x08, 0x15, 0x3D, 0x3E, 0x3C,
+    0x22, 0x1C, 0x05, 0x12, 0x19, 0x18, 0x17, 0x1B, 0x00, 0x03, 0x2E, 0x02, 0x16, 0x06, 0x34, 0x35,
+    0x23, 0x0F, 0x0E, 0x37, 0x0D, 0x27, 0x26, 0x20, 0x29, 0x04, 0x21, 0x24, 0x11, 0x2D, 0x2E, 0x1F,
+    0x2C, 0x1E, 0x39, 0x33, 0x07, 0x2A, 0x28, 0x1D, 0x0A, 0x2E, 0x32, 0x38, 0x13, 0x2B, 0x3F, 0x0C,
+];
+
+const RP2C04_0004_ORDER: [u8; 64] = [
+    0x18, 0x03, 0x1C, 0x28, 0x2E, 0x35, 0x01, 0x17, 0x10, 0x1F, 0x2A, 0x0E, 0x36, 0x37, 0x0B, 0x39,
+    0x25, 0x1E, 0x12, 0x34, 0x2E, 0x1D, 0x06, 0x26, 0x3E, 0x1B, 0x22, 0x19, 0x04, 0x2E, 0x3A, 0x21,
+    0x05, 0x0A, 0x07, 0x02, 0x13, 0x14, 0x00, 0x15, 0x0C, 0x3D, 0x11, 0x0F, 0x0D, 0x38, 0x2D, 0x24,
+    0x33, 0x20, 0x08, 0x16, 0x3F, 0x2B, 0x20, 0x3C, 0x2E, 0x27, 0x23, 0x31, 0x29, 0x32, 0x2C, 0x09,
+];
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum NametableMirroring {
@@ -128,6 +177,67 @@ pub enum VsPpuType
     RC2C05_05 = 0xC,
 }
 
+impl VsPpuType {
+    /// Rgb palette of the ppu
+    /// 
+    /// # Examples
+    /// 
+    ///  ```
+    /// let palette = nes_rom::ines::VsPpuType::RP2C03B.palette();
+    /// assert_eq!(palette[0x20], [255, 255, 255]);
+    ///  ```
+    pub fn palette(self) -> [[u8; 3]; 64] {
+        let order = match self {
+            VsPpuType::RP2C04_0001 => &RP2C04_0001_ORDER,
+            VsPpuType::RP2C04_0002 => &RP2C04_0002_ORDER,
+            VsPpuType::RP2C04_0003 => &RP2C04_0003_ORDER,
+            VsPpuType::RP2C04_0004 => &RP2C04_0004_ORDER,
+            // the RC2C05 ppus share the 2C03 palette
+            _ => return rgb_palette(RGB_PPU_PALETTE),
+        };
+
+        let mut colors: [u16; 64] = [0; 64];
+        for (color, index) in colors.iter_mut().zip(order.iter()) {
+            *color = RP2C04_PPU_COLORS[*index as usize];
+        }
+        rgb_palette(colors)
+    }
+}
+
+// expands the octal red, green and blue levels to 8 bits per channel
+fn rgb_palette(colors: [u16; 64]) -> [[u8; 3]; 64] {
+    let mut palette: [[u8; 3]; 64] = [[0; 3]; 64];
+    for (rgb, levels) in palette.iter_mut().zip(colors.iter()) {
+        for (i, channel) in rgb.iter_mut().enumerate() {
+            let level = (levels >> (6 - i * 3)) & 0x07;
+            *channel = (level * 255 / 7) as u8;
+        }
+    }
+    palette
+}
+
+// vs. system arcade board setup
+#[derive(Debug, Clone, PartialEq)]
+pub struct VsSystemInfo {
+    pub ppu: VsPpuType,
+    pub hardware: VsHardwareType,
+    pub palette: Box<[[u8; 3]; 64]>,
+    // dip switch settings the board starts with, switch 1 in bit 0 and a set bit for on.
+    // the header doesn't store them so all switches start off, set per game to override
+    pub dip_defaults: u8,
+}
+
+impl VsSystemInfo {
+    pub fn new(ppu: VsPpuType, hardware: VsHardwareType) -> VsSystemInfo {
+        VsSystemInfo {
+            ppu,
+            hardware,
+            palette: Box::new(ppu.palette()),
+            dip_defaults: 0,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Ram {
     Ines(u32),
@@ -142,8 +252,150 @@ pub enum HeaderVersion
 	ArchaiciNes,
 }
 
+// decoded contents of bytes 7-15 of an archaic ines header
+#[derive(Debug, Clone, PartialEq)]
+pub enum DirtyHeader
+{
+	DiskDude,
+	RipperTag(String),
+	Unknown([u8; 9]),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RomChip
+{
+	Trainer,
+	Prg,
+	Chr,
+}
+
+// header or data violations that are tolerated when parsing leniently
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseWarning
+{
+	VsAndPlaychoice,
+	MissingBatteryFlag { prg_nvram: u32, chr_nvram: u32 },
+	UnknownVsPpuType(u8),
+	UnknownVsHardwareType(u8),
+	UnknownConsoleType(u8),
+	UnknownGameInput(u8),
+	TruncatedData { chip: RomChip, offset: u64, expected: u32, found: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepairChange
+{
+	ClearedDirtyHeader(DirtyHeader),
+	BatteryFlag { from: bool, to: bool },
+	RomSize { chip: RomChip, from: u32, to: u32 },
+	// rom data missing from the end of file, zero filled
+	PaddedData { chip: RomChip, len: u32 },
+	TrailingDataAsChr { len: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RepairReport {
+    pub changes: Vec<RepairChange>,
+    // likely fixes that were not applied since they change how the game runs
+    pub suggestions: Vec<RepairChange>,
+}
+
+impl RepairReport {
+    pub fn is_clean(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MiscRomKind
+{
+	// vt369 console internal rom
+	Vt369Embedded,
+	// mapper 355, 3d-block pic16c54 protection mcu
+	ProtectionMcu,
+	// mapper 86 submapper 1, upd7756c speech samples
+	SpeechSamples,
+	// mapper 157, bandai datach barcode reader
+	DatachBarcode,
+	// second half of prg and chr rom, run by the sub cpu
+	VsDualSystemSubPrg,
+	VsDualSystemSubChr,
+	Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MiscRom {
+    pub kind: MiscRomKind,
+    pub data: Vec<u8>,
+}
+
+// playchoice-10 data stored after chr rom
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayChoiceData {
+    // 8kb instruction rom read by the menu cpu
+    pub inst_rom: Vec<u8>,
+    // 16 bytes each, missing in many dumps
+    pub prom_data: Option<[u8; 16]>,
+    pub prom_counter_out: Option<[u8; 16]>,
+}
+
+impl PlayChoiceData {
+    pub fn new() -> PlayChoiceData {
+        PlayChoiceData {
+            inst_rom: vec![0; PC10_INST_ROM_SIZE],
+            prom_data: None,
+            prom_counter_out: None,
+        }
+    }
+
+    // takes the playchoice data from the front of the data after chr rom
+    fn from_misc_data(misc_data: &mut Vec<u8>) -> PlayChoiceData {
+        let mut pc = PlayChoiceData::new();
+        pc.inst_rom = misc_data.drain(..PC10_INST_ROM_SIZE).collect();
+        if misc_data.len() >= 32 {
+            let mut prom_data: [u8; 16] = [0; 16];
+            let mut prom_counter_out: [u8; 16] = [0; 16];
+            prom_data.copy_from_slice(&misc_data[0..16]);
+            prom_counter_out.copy_from_slice(&misc_data[16..32]);
+            misc_data.drain(..32);
+            pc.prom_data = Some(prom_data);
+            pc.prom_counter_out = Some(prom_counter_out);
+        }
+        pc
+    }
+
+    /// Text of the instruction screens, the runs of at least 3 printable ascii characters in rom order
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let pc = nes_rom::ines::PlayChoiceData::new();
+    /// for line in pc.text_lines() {
+    ///     println!("{}", line);
+    /// }
+    ///  ```
+    pub fn text_lines(&self) -> Vec<String> {
+        self.inst_rom.split(|b| !(0x20..=0x7E).contains(b))
+            .map(|run| String::from_utf8_lossy(run).trim().to_string())
+            .filter(|line| line.len() >= 3)
+            .collect()
+    }
+
+    /// Game title shown in the menu, the first text line of the instruction rom
+    pub fn title(&self) -> Option<String> {
+        self.text_lines().into_iter().next()
+    }
+}
+
+impl Default for PlayChoiceData {
+    fn default() -> Self {
+        PlayChoiceData::new()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Ines {
+    pub raw_header: [u8; 16],
     pub header_version: HeaderVersion,
     pub mapper: u32,
     pub submapper: u8,
@@ -158,7 +410,10 @@ pub struct Ines {
     pub contains_nvram: bool,
     pub contains_trainer: bool,
     pub misc_rom_chips: u8,
-    pub vs_system: Option<(VsHardwareType, VsPpuType)>,
+    pub vs_system: Option<VsSystemInfo>,
+    pub playchoice: Option<PlayChoiceData>,
+    // rom chips shorter in the file than declared and the amount of bytes found, filled by lenient parsing
+    pub truncated: Vec<(RomChip, u32)>,
     pub prg_crc: u32,
     pub chr_crc: u32,
     pub trainer_data: Vec<u8>,
@@ -171,6 +426,7 @@ impl Ines {
 
     pub fn new() -> Ines {
         Ines {
+            raw_header: [0; 16],
             header_version: HeaderVersion::Ines,
             mapper: 0,
             submapper: 0,
@@ -186,6 +442,8 @@ impl Ines {
             contains_trainer: false,
             misc_rom_chips: 0,
             vs_system: None,
+            playchoice: None,
+            truncated: Vec::new(),
             prg_crc: 0,
             chr_crc: 0,
             trainer_data: Vec::new(),
@@ -199,58 +457,477 @@ impl Ines {
     /// 
     /// # Examples
     /// 
-    ///  ```
+    ///  ```no_run
+    /// # let arg = std::fs::File::open("rom.nes").unwrap();
     /// let ines = nes_rom::ines::Ines::from_rom(arg);
     ///  ```
-    pub fn from_rom<R: Read + Seek>(mut file: R) -> Result<Ines, RomError> {
+    pub fn from_rom<R: Read + Seek>(file: R) -> Result<Ines, RomError> {
+        let (ines, _) = Ines::from_rom_with(file, ParseOptions::default())?;
+        Ok(ines)
+    }
+
+    /// Load and parse .ines file, non-strict parsing loads broken roms and returns the violations found
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let arg = std::fs::File::open("rom.nes").unwrap();
+    /// let opts = nes_rom::ParseOptions { strict: false };
+    /// let (ines, warnings) = nes_rom::ines::Ines::from_rom_with(arg, opts).unwrap();
+    ///  ```
+    pub fn from_rom_with<R: Read + Seek>(mut file: R, opts: ParseOptions) -> Result<(Ines, Vec<ParseWarning>), RomError> {
         let mut buf: [u8; 16] = [0; 16];
-        file.read_exact(&mut buf)?;
+        file.read_exact(&mut buf).map_err(|e| RomError::from(e).with_format(RomFormat::Ines).with_field("header").with_offset(0))?;
+
+        let file_size = file.seek(io::SeekFrom::End(0))?;
+        file.seek(io::SeekFrom::Start(16))?;
+        let mut ctx = ParseContext { opts, warnings: Vec::new(), offset: 16, file_size };
 
         let mut ines;
-        if &buf[0..4] != super::INES_GUARD {
-            return Err(RomError::InvalidFormat);
+        if buf[0..4] != super::INES_GUARD {
+            return Err(ines_error(ErrorKind::InvalidFormat, "magic").with_offset(0)
+                .with_values(format!("{:02X?}", super::INES_GUARD), format!("{:02X?}", &buf[0..4])));
         }  
 
          // spec: if anything other than 0b00001000 than rom is ines
 		// will still need to be checked if dirty or corrupted rom header
 		if (buf[7] & 0x0C) == 0x08 {
-        ines = Ines::create_as_ines2(&mut buf)?;
+        ines = Ines::create_as_ines2(&buf, &mut ctx)?;
         }
         else if (buf[7] & 0x0C) == 0x00 && buf[15] == 0 && buf[14] == 0 && buf[13] == 0 && buf[12] == 0 {
-            ines = Ines::create_as_ines(&mut buf, false)?;
+            ines = Ines::create_as_ines(&buf, false, &mut ctx)?;
         }
         else {
-            ines = Ines::create_as_ines(&mut buf, true)?;
+            ines = Ines::create_as_ines(&buf, true, &mut ctx)?;
         }
 
+        ines.raw_header = buf;
+
         // copy rom data
-        ines.prg_data = vec![0u8; ines.prg_rom_size as usize];  
-        ines.chr_data = vec![0u8; ines.chr_rom_size as usize];
-        if ines.contains_trainer == true {
-            ines.trainer_data = vec![0u8; 512];
+        if ines.contains_trainer {
+            ines.trainer_data = ctx.read_rom_data(&mut file, 512, RomChip::Trainer, &mut ines.truncated)?;
         }
+        ines.prg_data = ctx.read_rom_data(&mut file, ines.prg_rom_size, RomChip::Prg, &mut ines.truncated)?;
+        ines.chr_data = ctx.read_rom_data(&mut file, ines.chr_rom_size, RomChip::Chr, &mut ines.truncated)?;
 
-        file.read_exact(&mut ines.trainer_data)?;
-        file.read_exact(&mut ines.prg_data)?;
-        file.read_exact(&mut ines.chr_data)?;
-
-        let curr = file.seek(io::SeekFrom::Current(0))?;
+        let curr = file.stream_position()?;
         let end = file.seek(io::SeekFrom::End(0))?;
         file.seek(io::SeekFrom::Start(curr))?;
 
         let len = (end - curr) as usize;
         if len > 0 {
             ines.misc_data = vec![0u8; len];
-            file.read_exact(&mut ines.misc_data)?;
+            file.read_exact(&mut ines.misc_data).map_err(|e| RomError::from(e).with_format(RomFormat::Ines).with_field("misc data").with_offset(curr))?;
+        }
+
+        if ines.console_type == ConsoleType::Playchoice && ines.misc_data.len() >= PC10_INST_ROM_SIZE {
+            ines.playchoice = Some(PlayChoiceData::from_misc_data(&mut ines.misc_data));
         }
 
         ines.prg_crc = crc32::crc32_ieee_from_slice(&ines.prg_data);     
         ines.chr_crc = crc32::crc32_ieee_from_slice(&ines.chr_data);           
 
-        Ok(ines)
+        Ok((ines, ctx.warnings))
     }
 
-    fn create_as_ines(header: &mut [u8], is_archaic: bool) -> Result<Ines, RomError> {
+    /// Rom areas besides prg and chr rom, interpreted by mapper and console type.
+    /// Data after chr rom that isn't known is returned as unknown, one entry per declared chip
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let ines = nes_rom::ines::Ines::new();
+    /// for rom in ines.misc_roms() {
+    ///     println!("{:?}: {} bytes", rom.kind, rom.data.len());
+    /// }
+    ///  ```
+    pub fn misc_roms(&self) -> Vec<MiscRom> {
+        let mut roms = Vec::new();
+
+        // main and sub cpu rom are stored back to back
+        if let Some(VsSystemInfo { hardware: VsHardwareType::VsDualSystem, .. }) = self.vs_system {
+            let prg_half = self.prg_data.len() / 2;
+            let chr_half = self.chr_data.len() / 2;
+            roms.push(MiscRom { kind: MiscRomKind::VsDualSystemSubPrg, data: self.prg_data[prg_half..].to_vec() });
+            roms.push(MiscRom { kind: MiscRomKind::VsDualSystemSubChr, data: self.chr_data[chr_half..].to_vec() });
+        }
+
+        if self.misc_data.is_empty() {
+            return roms;
+        }
+
+        let chips = self.misc_rom_chips as usize;
+        if self.header_version != HeaderVersion::Nes2 || chips == 0 {
+            roms.push(MiscRom { kind: MiscRomKind::Unknown, data: self.misc_data.clone() });
+            return roms;
+        }
+
+        // the known boards have a single misc. rom chip
+        if chips == 1 {
+            let kind = if self.console_type == ConsoleType::VT369 {
+                MiscRomKind::Vt369Embedded
+            }
+            else {
+                match (self.mapper, self.submapper) {
+                    (355, _) => MiscRomKind::ProtectionMcu,
+                    (86, 1) => MiscRomKind::SpeechSamples,
+                    (157, _) => MiscRomKind::DatachBarcode,
+                    _ => MiscRomKind::Unknown,
+                }
+            };
+            roms.push(MiscRom { kind, data: self.misc_data.clone() });
+            return roms;
+        }
+
+        // nes 2.0 leaves the layout of several chips to the board, without known sizes
+        // the data is split evenly, or kept whole if it can't be
+        let chip_len = if self.misc_data.len().is_multiple_of(chips) { self.misc_data.len() / chips } else { self.misc_data.len() };
+        for chunk in self.misc_data.chunks(chip_len) {
+            roms.push(MiscRom { kind: MiscRomKind::Unknown, data: chunk.to_vec() });
+        }
+
+        roms
+    }
+
+    /// Decode the bytes 7-15 ignored when the header was parsed as archaic
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let ines = nes_rom::ines::Ines::new();
+    /// if let Some(dirty) = ines.dirty_header() {
+    ///     println!("{:?}", dirty);
+    /// }
+    ///  ```
+    pub fn dirty_header(&self) -> Option<DirtyHeader> {
+        if self.header_version != HeaderVersion::ArchaiciNes {
+            return None;
+        }
+
+        let mut bytes: [u8; 9] = [0; 9];
+        bytes.copy_from_slice(&self.raw_header[7..16]);
+
+        if &bytes == b"DiskDude!" {
+            return Some(DirtyHeader::DiskDude);
+        }
+
+        // ripper tags are plain ascii text, possibly null padded
+        let text_len = bytes.iter().rposition(|b| *b != 0).map_or(0, |p| p + 1);
+        if text_len > 0 && bytes[..text_len].iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+            let tag = String::from_utf8_lossy(&bytes[..text_len]).trim().to_string();
+            return Some(DirtyHeader::RipperTag(tag));
+        }
+
+        Some(DirtyHeader::Unknown(bytes))
+    }
+
+    /// Normalize a dirty or inconsistent header, reporting each change made.
+    /// Rom sizes of truncated files are shrunk to the data found by lenient parsing
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let mut ines = nes_rom::ines::Ines::new();
+    /// let report = ines.repair();
+    /// for change in &report.changes {
+    ///     println!("{:?}", change);
+    /// }
+    ///  ```
+    pub fn repair(&mut self) -> RepairReport {
+        let mut report = RepairReport::default();
+
+        // garbage in bytes 7-15 e.g. "DiskDude!", write back as a clean ines header
+        if let Some(dirty) = self.dirty_header() {
+            self.header_version = HeaderVersion::Ines;
+            report.changes.push(RepairChange::ClearedDirtyHeader(dirty));
+        }
+
+        // battery bit must be set if and only if nes 2.0 nvram is present
+        if let Ram::Nes2{prg_nvram, chr_nvram, ..} = self.ram {
+            let has_nvram = prg_nvram > 0 || chr_nvram > 0;
+            if self.contains_nvram != has_nvram {
+                report.changes.push(RepairChange::BatteryFlag { from: self.contains_nvram, to: has_nvram });
+                self.contains_nvram = has_nvram;
+            }
+        }
+
+        // shrink truncated rom sizes to the data found, rounded up to whole banks
+        // but never past the declared size e.g. an exponent-multiplier size of 12kb
+        for (chip, found) in std::mem::take(&mut self.truncated) {
+            let (size, data, crc, unit) = match chip {
+                RomChip::Prg => (&mut self.prg_rom_size, &mut self.prg_data, &mut self.prg_crc, 16 * 1024),
+                RomChip::Chr => (&mut self.chr_rom_size, &mut self.chr_data, &mut self.chr_crc, 8 * 1024),
+                RomChip::Trainer => continue,
+            };
+
+            let repaired = (found.div_ceil(unit) * unit).min(*size);
+            if chip == RomChip::Prg && repaired == 0 {
+                continue;
+            }
+            if repaired < *size {
+                report.changes.push(RepairChange::RomSize { chip, from: *size, to: repaired });
+                *size = repaired;
+            }
+            if repaired > found {
+                report.changes.push(RepairChange::PaddedData { chip, len: repaired - found });
+            }
+            data.resize(repaired as usize, 0);
+            *crc = crc32::crc32_ieee_from_slice(data);
+        }
+
+        // data after chr rom that can't be misc. rom is most likely undeclared chr rom,
+        // only suggested as chr-ram carts may carry unrelated trailing data
+        let misc_len = self.misc_data.len();
+        if misc_len > 0 && misc_len.is_multiple_of(8 * 1024) && self.chr_rom_size > 0 && self.misc_rom_chips == 0 && self.console_type != ConsoleType::Playchoice {
+            report.suggestions.push(RepairChange::TrailingDataAsChr { len: misc_len as u32 });
+        }
+
+        if self.prg_rom_size as usize != self.prg_data.len() {
+            report.changes.push(RepairChange::RomSize { chip: RomChip::Prg, from: self.prg_rom_size, to: self.prg_data.len() as u32 });
+            self.prg_rom_size = self.prg_data.len() as u32;
+            self.prg_crc = crc32::crc32_ieee_from_slice(&self.prg_data);
+        }
+
+        if self.chr_rom_size as usize != self.chr_data.len() {
+            report.changes.push(RepairChange::RomSize { chip: RomChip::Chr, from: self.chr_rom_size, to: self.chr_data.len() as u32 });
+            self.chr_rom_size = self.chr_data.len() as u32;
+            self.chr_crc = crc32::crc32_ieee_from_slice(&self.chr_data);
+        }
+
+        report
+    }
+
+    /// Serialize to .ines file bytes
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let ines = nes_rom::ines::Ines::new();
+    /// let bytes = ines.to_bytes().unwrap();
+    ///  ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, RomError> {
+        let pc_len = self.playchoice.as_ref().map_or(0, |pc| pc.inst_rom.len() + 32);
+        let mut bytes = Vec::with_capacity(16 + self.trainer_data.len() + self.prg_data.len() + self.chr_data.len() + pc_len + self.misc_data.len());
+        self.write_to(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Write .ines file, header format is chosen by header version
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let ines = nes_rom::ines::Ines::new();
+    /// let file = std::fs::File::create("rom.nes").unwrap();
+    /// ines.write_to(file).unwrap();
+    ///  ```
+    pub fn write_to<W: Write>(&self, mut file: W) -> Result<(), RomError> {
+        let mut header: [u8; 16] = [0; 16];
+        header[0..4].copy_from_slice(&super::INES_GUARD);
+
+        match self.header_version {
+            HeaderVersion::Nes2 => self.write_ines2_header(&mut header)?,
+            // archaic roms are written with the cleared header the parser interpreted
+            HeaderVersion::Ines | HeaderVersion::ArchaiciNes => self.write_ines_header(&mut header)?,
+        }
+
+        if self.contains_trainer && self.trainer_data.len() != 512 {
+            return Err(ines_error(ErrorKind::InvalidRom, "trainer data").with_values(512, self.trainer_data.len()));
+        }
+        if let Some(ref pc) = self.playchoice {
+            if pc.inst_rom.len() != PC10_INST_ROM_SIZE {
+                return Err(ines_error(ErrorKind::InvalidRom, "playchoice inst-rom").with_values(PC10_INST_ROM_SIZE, pc.inst_rom.len()));
+            }
+        }
+
+        file.write_all(&header)?;
+        if self.contains_trainer {
+            file.write_all(&self.trainer_data)?;
+        }
+        file.write_all(&self.prg_data)?;
+        file.write_all(&self.chr_data)?;
+        if let Some(ref pc) = self.playchoice {
+            file.write_all(&pc.inst_rom)?;
+            if let (Some(prom_data), Some(prom_counter_out)) = (pc.prom_data, pc.prom_counter_out) {
+                file.write_all(&prom_data)?;
+                file.write_all(&prom_counter_out)?;
+            }
+        }
+        file.write_all(&self.misc_data)?;
+
+        Ok(())
+    }
+
+    /// Convert ines to unif format, clone rom data
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let ines = nes_rom::ines::Ines::new();
+    /// let unif = ines.to_unif();
+    ///  ```
+    pub fn to_unif(&self) -> Result<unif::Unif, RomError> {
+        let mut u = unif::Unif::new();
+
+        // unif board mappings list ram sizes in kb, ines roms without chr rom have 8kb chr ram
+        let (prg_ram, chr_ram) = match self.ram {
+            Ram::Ines(prg_ram) => (prg_ram / 1024, if self.chr_data.is_empty() { 8 } else { 0 }),
+            Ram::Nes2{prg_ram, prg_nvram, chr_ram, chr_nvram} => ((prg_ram + prg_nvram) / 1024, (chr_ram + chr_nvram) / 1024),
+        };
+        u.mapr = unif::get_board_name(self.mapper, self.submapper, self.prg_data.len(), prg_ram, chr_ram)?;
+        u.batr = Some(self.contains_nvram);
+
+        u.tvci = match self.tv_system {
+            TvSystem::NTSC => Some(unif::TvSystem::NTSC),
+            TvSystem::PAL | TvSystem::Dendy => Some(unif::TvSystem::PAL),
+            TvSystem::MultRegion => Some(unif::TvSystem::MultRegion),
+        };
+
+        u.ctrl = match self.game_input {
+            GameInputType::StandardControllers => Some(unif::GameInputType::StandardController),
+            GameInputType::Zapper => Some(unif::GameInputType::Zapper),
+            GameInputType::RobGyroSet => Some(unif::GameInputType::ROB),
+            GameInputType::ArkanoidControllerNes | GameInputType::ArkanoidControllerFamicom => Some(unif::GameInputType::Arkanoid),
+            GameInputType::PowerPadSideA | GameInputType::PowerPadSideB => Some(unif::GameInputType::PowerPad),
+            GameInputType::FourScore => Some(unif::GameInputType::FourScore),
+            _ => None,
+        };
+
+        u.mirr = match self.nametable_mirroring {
+            NametableMirroring::Horizontal => Some(unif::NametableMirroring::Horizontal),
+            NametableMirroring::Vertical => Some(unif::NametableMirroring::Vertical),
+            NametableMirroring::FourScreens => Some(unif::NametableMirroring::FourScreens),
+            NametableMirroring::Other => Some(unif::NametableMirroring::MapperControlled),
+        };
+
+        u.prg_crc = self.prg_crc;
+        u.chr_crc = self.chr_crc;
+        u.prg_data = self.prg_data.clone();
+        u.chr_data = self.chr_data.clone();
+
+        Ok(u)
+    }
+
+    fn write_ines_header(&self, header: &mut [u8]) -> Result<(), RomError> {
+        let prg_units = rom_size_units(self.prg_data.len(), 16 * 1024, 0xFF, RomChip::Prg)?;
+        let chr_units = rom_size_units(self.chr_data.len(), 8 * 1024, 0xFF, RomChip::Chr)?;
+        if self.mapper > 0xFF {
+            return Err(ines_error(ErrorKind::InvalidConversion, "mapper").with_values("at most 255", self.mapper));
+        }
+
+        header[4] = prg_units as u8;
+        header[5] = chr_units as u8;
+
+        // flag 6
+        header[6] = self.flags_6();
+
+        if self.header_version == HeaderVersion::ArchaiciNes {
+            return Ok(());
+        }
+
+        // flag 7
+        header[7] = (self.mapper as u8) & 0xF0;
+        match self.console_type {
+            ConsoleType::Regular => (),
+            ConsoleType::VsSystem => header[7] |= 0b00000001,
+            ConsoleType::Playchoice => header[7] |= 0b00000010,
+            ct => return Err(ines_error(ErrorKind::InvalidConversion, "console type").with_values("Regular, VsSystem or Playchoice", format!("{:?}", ct))),
+        }
+
+        // flag 8
+        if let Ram::Ines(prg_ram) = self.ram {
+            header[8] = (prg_ram / (8 * 1024)) as u8;
+        }
+
+        // flag 9
+        if self.tv_system == TvSystem::PAL {
+            header[9] = 0b00000001;
+        }
+
+        Ok(())
+    }
+
+    fn write_ines2_header(&self, header: &mut [u8]) -> Result<(), RomError> {
+        let (prg_lsb, prg_msb) = nes2_rom_size_bytes(self.prg_data.len(), 16 * 1024, RomChip::Prg)?;
+        let (chr_lsb, chr_msb) = nes2_rom_size_bytes(self.chr_data.len(), 8 * 1024, RomChip::Chr)?;
+        if self.mapper > 0xFFF {
+            return Err(ines_error(ErrorKind::InvalidConversion, "mapper").with_values("at most 4095", self.mapper));
+        }
+
+        header[4] = prg_lsb;
+        header[5] = chr_lsb;
+
+        // flag 6
+        header[6] = self.flags_6();
+
+        // flag 7
+        header[7] = ((self.mapper as u8) & 0xF0) | 0b00001000;
+        match self.console_type {
+            ConsoleType::Regular => (),
+            ConsoleType::VsSystem => header[7] |= 0b00000001,
+            ConsoleType::Playchoice => header[7] |= 0b00000010,
+            ct @ ConsoleType::FDS | ct @ ConsoleType::Unknown =>
+                return Err(ines_error(ErrorKind::InvalidConversion, "console type").with_values("nes 2.0 console type", format!("{:?}", ct))),
+            _ => header[7] |= 0b00000011,
+        }
+
+        // flag 8
+        header[8] = (((self.mapper >> 8) & 0x0F) as u8) | (self.submapper << 4);
+
+        // flag 9
+        header[9] = (chr_msb << 4) | prg_msb;
+
+        // flag 10
+        // flag 11
+        if let Ram::Nes2{prg_ram, prg_nvram, chr_ram, chr_nvram} = self.ram {
+            header[10] = (nes2_ram_shift(prg_nvram) << 4) | nes2_ram_shift(prg_ram);
+            header[11] = (nes2_ram_shift(chr_nvram) << 4) | nes2_ram_shift(chr_ram);
+        }
+
+        // flag 12
+        header[12] = self.tv_system as u8;
+
+        // flag 13
+        if (header[7] & 0b00000011) == 1 {
+            if let Some(ref vs) = self.vs_system {
+                header[13] = ((vs.hardware as u8) << 4) | (vs.ppu as u8);
+            }
+        }
+        else if (header[7] & 0b00000011) == 3 {
+            header[13] = self.console_type as u8;
+        }
+
+        // flag 14
+        header[14] = self.misc_rom_chips & 0b00000011;
+
+        // flag 15
+        header[15] = (self.game_input as u8) & 0b00111111;
+
+        Ok(())
+    }
+
+    fn flags_6(&self) -> u8 {
+        let mut flags = ((self.mapper as u8) & 0x0F) << 4;
+
+        if self.contains_nvram {
+            flags |= 0b00000010;
+        }
+
+        if self.contains_trainer {
+            flags |= 0b00000100;
+        }
+
+        match self.nametable_mirroring {
+            NametableMirroring::Horizontal => (),
+            NametableMirroring::Vertical => flags |= 0b00000001,
+            NametableMirroring::FourScreens => flags |= 0b00001000,
+            NametableMirroring::Other => flags |= 0b00001001,
+        }
+
+        flags
+    }
+
+    fn create_as_ines(header: &[u8], is_archaic: bool, ctx: &mut ParseContext) -> Result<Ines, RomError> {
         let mut ines = Ines::new();
 
         ines.header_version = HeaderVersion::Ines;
@@ -282,18 +959,8 @@ impl Ines {
         ines.prg_rom_size = (ines.prg_rom_size * 16) * 1024;
         ines.chr_rom_size = (ines.chr_rom_size * 8) * 1024;
 
-        if is_archaic == true {
-            // "dirty rom" clear last 9 bytes
-            header[7] = 0;
-            header[8] = 0;
-            header[9] = 0;
-            header[10] = 0;
-            header[11] = 0;
-            header[12] = 0;
-            header[13] = 0;
-            header[14] = 0;
-            header[15] = 0;
-
+        if is_archaic {
+            // "dirty rom" ignore last 9 bytes, they are kept in the raw header
             ines.header_version = HeaderVersion::ArchaiciNes;
             return Ok(ines);
         }
@@ -301,13 +968,14 @@ impl Ines {
         // flag 7
         // rom cannot be vs and playchoice
         if (header[7] & 0b00000011) == 0b00000011 {
-            return Err(RomError::InvalidRom);
+            ctx.violation(ParseWarning::VsAndPlaychoice)?;
+            ines.console_type = ConsoleType::Regular;
         }
         else if (header[7] & 0b00000001) == 0b00000001 {
-            ines.console_type = ConsoleType::Playchoice;
+            ines.console_type = ConsoleType::VsSystem;
         }
         else if (header[7] & 0b00000010) == 0b00000010 {
-            ines.console_type = ConsoleType::VsSystem;
+            ines.console_type = ConsoleType::Playchoice;
         }
         else {
             ines.console_type = ConsoleType::Regular
@@ -331,12 +999,10 @@ impl Ines {
         Ok(ines)
     }
 
-    fn create_as_ines2(header: &mut [u8]) -> Result<Ines, RomError> {
+    fn create_as_ines2(header: &[u8], ctx: &mut ParseContext) -> Result<Ines, RomError> {
         let mut ines = Ines::new();
 
         ines.header_version = HeaderVersion::Nes2;
-        ines.prg_rom_size = header[4] as u32;
-        ines.chr_rom_size = header[5] as u32;
 
         // flag 6
         if (header[6] & 0b00000010) == 0b00000010 {
@@ -350,6 +1016,9 @@ impl Ines {
         let low_nibble = (header[6] & 0b11110000) >> 4;
         ines.mapper |= low_nibble as u32;
 
+        let high_nibble = header[7] & 0xF0;
+        ines.mapper |= high_nibble as u32;
+
          // following Mesen source - http://wiki.nesdev.com/w/index.php/Talk:NES_2.0
         // TODO possibly change mirroring
         if (header[6] & 0b00001001) == 0b00001001 {
@@ -368,58 +1037,61 @@ impl Ines {
         //flag 8
         let higher_nibble = ((header[8] & 0b00001111) as u32) << 8;
         ines.mapper |= higher_nibble;
-        ines.submapper = ((header[8] & 0b11110000) >> 4) as u8;
+        ines.submapper = (header[8] & 0b11110000) >> 4;
 
         //flag 9
-        let higher_chr_rom_size = ((header[9] & 0b11110000) as u32) << 4;
-        let higher_prg_rom_size = ((header[9] & 0b00001111) as u32) << 8;
-        ines.chr_rom_size |= higher_chr_rom_size;
-        ines.prg_rom_size |= higher_prg_rom_size;
+        ines.prg_rom_size = nes2_rom_size(header[4], header[9] & 0b00001111, 16 * 1024, RomChip::Prg)?;
+        ines.chr_rom_size = nes2_rom_size(header[5], (header[9] & 0b11110000) >> 4, 8 * 1024, RomChip::Chr)?;
 
         // flag 10
         // flag 11
-        ines.ram = Ram::Nes2 {
-            prg_nvram: 64 << (((header[10] & 0b11110000) >> 4) as u32),
-            prg_ram: 64 << ((header[10] & 0b00001111) as u32),
-            chr_nvram: 64 << (((header[11] & 0b11110000) >> 4) as u32),
-            chr_ram: 64 << ((header[11] & 0b00001111) as u32),
-        };
+        let prg_nvram = nes2_ram_size((header[10] & 0b11110000) >> 4);
+        let prg_ram = nes2_ram_size(header[10] & 0b00001111);
+        let chr_nvram = nes2_ram_size((header[11] & 0b11110000) >> 4);
+        let chr_ram = nes2_ram_size(header[11] & 0b00001111);
 
-        match ines.ram {
-            Ram::Nes2{prg_nvram: p, chr_nvram: c, ..} => {
-                //For backward compatibility, the battery bit in the original iNES header (byte 6, bit 1) MUST be true if the upper nibble of byte 10 or 11 is nonzero or false otherwise
-                if ines.contains_nvram == false && (p > 0 || c > 0)  {
-                    return Err(RomError::InvalidRom);
-                }
-            },
-            _ => (),
+        //For backward compatibility, the battery bit in the original iNES header (byte 6, bit 1) MUST be true if the upper nibble of byte 10 or 11 is nonzero or false otherwise
+        if !ines.contains_nvram && (prg_nvram > 0 || chr_nvram > 0) {
+            ctx.violation(ParseWarning::MissingBatteryFlag { prg_nvram, chr_nvram })?;
         }
 
+        ines.ram = Ram::Nes2 { prg_ram, prg_nvram, chr_ram, chr_nvram };
+
         // flag 12
         let timing_mode = header[12] & 0b00000011;
         ines.tv_system = match TvSystem::from_u8(timing_mode) {
             Some(tv) => tv,
-            None => return Err(RomError::InvalidRom),
+            None => return Err(ines_error(ErrorKind::InvalidRom, "timing mode").with_offset(12).with_values("0-3", timing_mode)),
         };
 
         // flag 13
         if (header[7] & 0b00000011) == 1 {
-            let ppu_type = match VsPpuType::from_u8(header[13] & 0b00001111) {
-                Some(ppu) => ppu,
-                None => return Err(RomError::InvalidRom),
-            };
+            ines.console_type = ConsoleType::VsSystem;
 
-            let ppu_hw = match VsHardwareType::from_u8(header[13] & 0b11110000) {
-                Some(ppu) => ppu,
-                None => return Err(RomError::InvalidRom),
-            };
+            let ppu_type = VsPpuType::from_u8(header[13] & 0b00001111);
+            if ppu_type.is_none() {
+                ctx.violation(ParseWarning::UnknownVsPpuType(header[13] & 0b00001111))?;
+            }
 
-            ines.vs_system = Some((ppu_hw, ppu_type));     
+            let hw_type = VsHardwareType::from_u8(header[13] >> 4);
+            if hw_type.is_none() {
+                ctx.violation(ParseWarning::UnknownVsHardwareType(header[13] >> 4))?;
+            }
+
+            if let (Some(hw_type), Some(ppu_type)) = (hw_type, ppu_type) {
+                ines.vs_system = Some(VsSystemInfo::new(ppu_type, hw_type));
+            }
+        }
+        else if (header[7] & 0b00000011) == 2 {
+            ines.console_type = ConsoleType::Playchoice;
         }
         else if (header[7] & 0b00000011) == 3 {
             ines.console_type = match ConsoleType::from_u8(header[13] & 0b00001111) {
                 Some(gs) => gs,
-                None => return Err(RomError::InvalidRom),
+                None => {
+                    ctx.violation(ParseWarning::UnknownConsoleType(header[13] & 0b00001111))?;
+                    ConsoleType::Unknown
+                },
             };
         }
 
@@ -428,18 +1100,470 @@ impl Ines {
         ines.misc_rom_chips = header[14] & 0b00000011;
 
         // flag 15
-        ines.game_input = match GameInputType::from_u8(header[15] * 0b00111111) {
+        ines.game_input = match GameInputType::from_u8(header[15] & 0b00111111) {
             Some(gi) => gi,
-            None => return Err(RomError::InvalidRom),
+            None => {
+                ctx.violation(ParseWarning::UnknownGameInput(header[15] & 0b00111111))?;
+                GameInputType::Unspecified
+            },
         };
 
-        ines.prg_rom_size = (ines.prg_rom_size * 16) * 1024;
-        ines.chr_rom_size = (ines.chr_rom_size * 8) * 1024;
-
         Ok(ines)
     }
 }
 
+impl Default for Ines {
+    fn default() -> Self {
+        Ines::new()
+    }
+}
+
+impl RomInfo for Ines {
+    fn format(&self) -> RomFormat {
+        RomFormat::Ines
+    }
+
+    fn prg(&self) -> &[u8] {
+        &self.prg_data
+    }
+
+    fn chr(&self) -> &[u8] {
+        &self.chr_data
+    }
+
+    fn prg_crc(&self) -> u32 {
+        self.prg_crc
+    }
+
+    fn chr_crc(&self) -> u32 {
+        self.chr_crc
+    }
+
+    fn mapper(&self) -> Option<u32> {
+        Some(self.mapper)
+    }
+
+    fn submapper(&self) -> Option<u8> {
+        Some(self.submapper)
+    }
+
+    fn board(&self) -> Option<&str> {
+        None
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        match self.nametable_mirroring {
+            NametableMirroring::Horizontal => Mirroring::Horizontal,
+            NametableMirroring::Vertical => Mirroring::Vertical,
+            NametableMirroring::FourScreens => Mirroring::FourScreens,
+            NametableMirroring::Other => Mirroring::MapperControlled,
+        }
+    }
+
+    fn region(&self) -> Region {
+        match self.tv_system {
+            TvSystem::NTSC => Region::NTSC,
+            TvSystem::PAL => Region::PAL,
+            TvSystem::MultRegion => Region::MultRegion,
+            TvSystem::Dendy => Region::Dendy,
+        }
+    }
+
+    fn has_battery(&self) -> bool {
+        self.contains_nvram
+    }
+}
+
+impl ParseWarning {
+    fn to_error(&self) -> RomError {
+        match *self {
+            ParseWarning::VsAndPlaychoice =>
+                ines_error(ErrorKind::InvalidRom, "console type").with_offset(7).with_values("Vs. System or Playchoice", "both"),
+            ParseWarning::MissingBatteryFlag { prg_nvram, chr_nvram } =>
+                ines_error(ErrorKind::InvalidRom, "battery flag").with_offset(6)
+                    .with_values(format!("set for prg nvram {} chr nvram {}", prg_nvram, chr_nvram), "clear"),
+            ParseWarning::UnknownVsPpuType(ppu) =>
+                ines_error(ErrorKind::InvalidRom, "Vs. PPU type").with_offset(13).with_values("0-12", ppu),
+            ParseWarning::UnknownVsHardwareType(hw) =>
+                ines_error(ErrorKind::InvalidRom, "Vs. hardware type").with_offset(13).with_values("0-6", hw),
+            ParseWarning::UnknownConsoleType(ct) =>
+                ines_error(ErrorKind::InvalidRom, "extended console type").with_offset(13).with_values("0-10", ct),
+            ParseWarning::UnknownGameInput(gi) =>
+                ines_error(ErrorKind::InvalidRom, "default expansion device").with_offset(15).with_values("0-46", gi),
+            ParseWarning::TruncatedData { chip, offset, expected, found } =>
+                ines_error(ErrorKind::InvalidRom, format!("{:?} data", chip)).with_offset(offset).with_values(expected, found),
+        }
+    }
+}
+
+struct ParseContext {
+    opts: ParseOptions,
+    warnings: Vec<ParseWarning>,
+    offset: u64,
+    file_size: u64,
+}
+
+impl ParseContext {
+    // strict parsing fails on any violation, otherwise it is recorded and parsing continues
+    fn violation(&mut self, warning: ParseWarning) -> Result<(), RomError> {
+        if self.opts.strict {
+            return Err(warning.to_error());
+        }
+
+        self.warnings.push(warning);
+        Ok(())
+    }
+
+    // reads the declared amount of rom data, data missing from the end of file is a violation.
+    // only the bytes left in the file are allocated up front, nes 2.0 headers can declare gigabytes,
+    // and missing data is zero padded only up to MAX_PADDED_ROM_SIZE
+    fn read_rom_data<R: Read>(&mut self, file: &mut R, size: u32, chip: RomChip, truncated: &mut Vec<(RomChip, u32)>) -> Result<Vec<u8>, RomError> {
+        let offset = self.offset;
+        self.offset += size as u64;
+
+        let available = self.file_size.saturating_sub(offset);
+        let mut data = vec![0u8; (size as u64).min(available) as usize];
+        let mut found = 0;
+        while found < data.len() {
+            match file.read(&mut data[found..]) {
+                Ok(0) => break,
+                Ok(n) => found += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(RomError::from(e).with_format(RomFormat::Ines).with_field(format!("{:?} data", chip)).with_offset(offset)),
+            }
+        }
+
+        if found < size as usize {
+            self.violation(ParseWarning::TruncatedData { chip, offset, expected: size, found: found as u32 })?;
+            truncated.push((chip, found as u32));
+            data.truncate(found);
+            if size <= MAX_PADDED_ROM_SIZE {
+                data.resize(size as usize, 0);
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+// nes 2.0 ram sizes are stored as a shift count, zero meaning no ram
+fn nes2_ram_size(shift: u8) -> u32 {
+    if shift == 0 {
+        0
+    }
+    else {
+        64 << (shift as u32)
+    }
+}
+
+// smallest shift count that fits the ram size
+fn nes2_ram_shift(size: u32) -> u8 {
+    let mut shift = 1;
+    if size == 0 {
+        return 0;
+    }
+
+    while shift < 0x0F && (64 << shift) < size {
+        shift += 1;
+    }
+
+    shift
+}
+
+fn rom_size_units(len: usize, unit_size: usize, max_units: usize, chip: RomChip) -> Result<usize, RomError> {
+    let units = len / unit_size;
+    if !len.is_multiple_of(unit_size) || units > max_units {
+        return Err(ines_error(ErrorKind::InvalidConversion, format!("{:?} rom size", chip))
+            .with_values(format!("multiple of {} up to {} units", unit_size, max_units), len));
+    }
+
+    Ok(units)
+}
+
+// an msb nibble of 0xF switches to the exponent-multiplier form, 2^E * (MM*2+1) bytes
+fn nes2_rom_size(lsb: u8, msb: u8, unit_size: u32, chip: RomChip) -> Result<u32, RomError> {
+    if msb == 0x0F {
+        let exponent = (lsb >> 2) as u32;
+        let multiplier = ((lsb & 0b00000011) as u64) * 2 + 1;
+        let size = 1u64.checked_shl(exponent).map(|s| s * multiplier).unwrap_or(u64::MAX);
+        if size > u32::MAX as u64 {
+            let offset = if chip == RomChip::Prg { 4 } else { 5 };
+            return Err(ines_error(ErrorKind::InvalidRom, format!("{:?} rom size", chip)).with_offset(offset)
+                .with_values("at most 4 GiB", format!("2^{} * {}", exponent, multiplier)));
+        }
+
+        Ok(size as u32)
+    }
+    else {
+        Ok((((msb as u32) << 8) | lsb as u32) * unit_size)
+    }
+}
+
+// encode rom size as header lsb byte and msb nibble, using the exponent-multiplier form
+// when the size is not a multiple of the unit size or too large for the simple form
+fn nes2_rom_size_bytes(len: usize, unit_size: usize, chip: RomChip) -> Result<(u8, u8), RomError> {
+    if let Ok(units) = rom_size_units(len, unit_size, 0xEFF, chip) {
+        return Ok(((units & 0xFF) as u8, (units >> 8) as u8));
+    }
+
+    let exponent = len.trailing_zeros();
+    let multiplier = len >> exponent;
+    if len == 0 || exponent > 0b00111111 || multiplier > 7 {
+        return Err(ines_error(ErrorKind::InvalidConversion, format!("{:?} rom size", chip))
+            .with_values("2^E * (MM*2+1) bytes", len));
+    }
+
+    Ok((((exponent as u8) << 2) | (((multiplier - 1) / 2) as u8), 0x0F))
+}
+
+fn ines_error<S: Into<String>>(kind: ErrorKind, field: S) -> RomError {
+    RomError::new(kind).with_format(RomFormat::Ines).with_field(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn example_roms_round_trip() {
+        let mut checked = 0;
+        for entry in fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/example_roms")).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "nes") {
+                continue;
+            }
+
+            let bytes = fs::read(&path).unwrap();
+            let ines = Ines::from_rom(io::Cursor::new(&bytes)).unwrap();
+            assert!(ines.to_bytes().unwrap() == bytes, "{} is not written back byte-identical", path.display());
+            checked += 1;
+        }
+
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn to_unif_board_names() {
+        // ines rom with the given mapper, flag 6/7 mapper bits, prg units and prg ram units
+        let rom = |mapper: u8, prg_units: u8, prg_ram_units: u8| {
+            let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, prg_units, 1, mapper << 4, mapper & 0xF0, prg_ram_units, 0, 0, 0, 0, 0, 0, 0];
+            rom.resize(16 + prg_units as usize * 0x4000 + 0x2000, 0xEA);
+            Ines::from_rom(io::Cursor::new(rom)).unwrap()
+        };
 
+        let simpsons = fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/example_roms/Simpsons, The - Bart vs. the Space Mutants (USA).nes")).unwrap();
+        let boards = [
+            (rom(0, 1, 0), "NES-NROM-128"),
+            (rom(0, 2, 0), "NES-NROM-256"),
+            (Ines::from_rom(io::Cursor::new(simpsons)).unwrap(), "NES-SLROM"),
+            (rom(1, 8, 1), "NES-SKROM"),
+            (rom(4, 8, 0), "NES-TLROM"),
+            (rom(4, 8, 1), "NES-TKROM"),
+        ];
 
+        for (ines, board) in boards.iter() {
+            let u = ines.to_unif().unwrap();
+            assert_eq!(u.mapr, *board);
 
+            // the board maps back to the same mapper
+            let u = unif::Unif::from_rom(io::Cursor::new(u.to_bytes().unwrap())).unwrap();
+            assert_eq!(u.mapper(), Some(ines.mapper));
+        }
+    }
+
+    #[test]
+    fn strict_truncated_data_reports_sizes() {
+        // 32kb prg, 8kb chr declared, 20000 bytes of prg present
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        rom.resize(16 + 20000, 0xEA);
+
+        let e = Ines::from_rom(io::Cursor::new(&rom)).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidRom);
+        assert_eq!(e.field(), Some("Prg data"));
+        assert_eq!(e.offset(), Some(16));
+        assert_eq!(e.expected(), Some("32768"));
+        assert_eq!(e.found(), Some("20000"));
+    }
+
+    #[test]
+    fn nes2_rom_size_is_checked_against_file_length() {
+        // exponent-multiplier prg size of 2^31 bytes in a 32 byte file
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 0x7C, 0, 0, 0x08, 0, 0x0F, 0, 0, 0, 0, 0, 0];
+        rom.resize(32, 0);
+
+        let e = Ines::from_rom(io::Cursor::new(&rom)).unwrap_err();
+        assert_eq!(e.field(), Some("Prg data"));
+        assert_eq!(e.expected(), Some("2147483648"));
+        assert_eq!(e.found(), Some("16"));
+    }
+
+    #[test]
+    fn lenient_padding_is_limited() {
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 0x7C, 0, 0, 0x08, 0, 0x0F, 0, 0, 0, 0, 0, 0];
+        rom.resize(32, 0xEA);
+
+        let (ines, warnings) = Ines::from_rom_with(io::Cursor::new(&rom), ParseOptions { strict: false }).unwrap();
+        assert_eq!(ines.prg_rom_size, 0x8000_0000);
+        assert_eq!(ines.prg_data, vec![0xEA; 16]);
+        assert_eq!(ines.truncated, vec![(RomChip::Prg, 16)]);
+        assert_eq!(warnings, vec![ParseWarning::TruncatedData { chip: RomChip::Prg, offset: 16, expected: 0x8000_0000, found: 16 }]);
+
+        // 32kb prg, 8kb chr declared, 20000 bytes of prg present
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        rom.resize(16 + 20000, 0xEA);
+
+        let (ines, warnings) = Ines::from_rom_with(io::Cursor::new(&rom), ParseOptions { strict: false }).unwrap();
+        assert_eq!(ines.prg_data.len(), 0x8000);
+        assert_eq!(ines.chr_data, vec![0; 0x2000]);
+        assert_eq!(ines.truncated, vec![(RomChip::Prg, 20000), (RomChip::Chr, 0)]);
+        assert_eq!(warnings.len(), 2);
+    }
+
+    // nes 2.0 vs. system rom with 32kb prg and 8kb chr
+    fn vs_rom(flag_13: u8) -> Vec<u8> {
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 2, 1, 0, 0x09, 0, 0, 0, 0, 0, flag_13, 0, 0];
+        rom.resize(16 + 0x8000 + 0x2000, 0xEA);
+        rom
+    }
+
+    #[test]
+    fn nes2_vs_system_header() {
+        // dual system hardware with an RP2C04-0002 ppu
+        let rom = vs_rom(0x53);
+        let ines = Ines::from_rom(io::Cursor::new(&rom)).unwrap();
+        assert_eq!(ines.header_version, HeaderVersion::Nes2);
+        assert_eq!(ines.console_type, ConsoleType::VsSystem);
+
+        let vs = ines.vs_system.as_ref().unwrap();
+        assert_eq!(vs.ppu, VsPpuType::RP2C04_0002);
+        assert_eq!(vs.hardware, VsHardwareType::VsDualSystem);
+        assert_eq!(*vs.palette, VsPpuType::RP2C04_0002.palette());
+        assert_eq!(vs.dip_defaults, 0);
+
+        assert_eq!(ines.to_bytes().unwrap(), rom);
+    }
+
+    #[test]
+    fn nes2_vs_system_unknown_types() {
+        let rom = vs_rom(0xDF);
+        assert!(Ines::from_rom(io::Cursor::new(&rom)).is_err());
+
+        let (ines, warnings) = Ines::from_rom_with(io::Cursor::new(&rom), ParseOptions { strict: false }).unwrap();
+        assert_eq!(ines.console_type, ConsoleType::VsSystem);
+        assert_eq!(ines.vs_system, None);
+        assert_eq!(warnings, vec![ParseWarning::UnknownVsPpuType(0xF), ParseWarning::UnknownVsHardwareType(0xD)]);
+    }
+
+    // octal color of a palette entry
+    fn octal(rgb: [u8; 3]) -> u16 {
+        rgb.iter().fold(0, |color, channel| (color << 3) | ((*channel as u16 * 7 + 127) / 255))
+    }
+
+    #[test]
+    fn rp2c04_palettes() {
+        // first row of each palette as listed in the nesdev wiki
+        let rows = [
+            (VsPpuType::RP2C04_0001, [0o755, 0o637, 0o700, 0o447, 0o044, 0o120, 0o222, 0o704, 0o777, 0o333, 0o750, 0o503, 0o403, 0o660, 0o320, 0o777]),
+            (VsPpuType::RP2C04_0002, [0o000, 0o750, 0o430, 0o572, 0o473, 0o737, 0o044, 0o567, 0o700, 0o407, 0o773, 0o747, 0o777, 0o637, 0o467, 0o040]),
+            (VsPpuType::RP2C04_0003, [0o507, 0o737, 0o473, 0o555, 0o040, 0o777, 0o567, 0o120, 0o014, 0o000, 0o764, 0o320, 0o704, 0o666, 0o653, 0o467]),
+        ];
+        for (ppu, row) in rows.iter() {
+            let palette = ppu.palette();
+            let found: Vec<u16> = palette[0..16].iter().map(|rgb| octal(*rgb)).collect();
+            assert_eq!(found, row.to_vec(), "{:?}", ppu);
+        }
+
+        // all four hold the same colors in a different order
+        let mut sorted: Vec<Vec<[u8; 3]>> = [VsPpuType::RP2C04_0001, VsPpuType::RP2C04_0002, VsPpuType::RP2C04_0003, VsPpuType::RP2C04_0004]
+            .iter()
+            .map(|ppu| {
+                let mut colors = ppu.palette().to_vec();
+                colors.sort();
+                colors
+            })
+            .collect();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 1);
+    }
+
+    #[test]
+    fn misc_roms_are_split_per_chip() {
+        let mut ines = Ines::new();
+        ines.header_version = HeaderVersion::Nes2;
+        ines.mapper = 355;
+        ines.misc_rom_chips = 1;
+        ines.misc_data = vec![0xAA; 0x400];
+        assert_eq!(ines.misc_roms(), vec![MiscRom { kind: MiscRomKind::ProtectionMcu, data: vec![0xAA; 0x400] }]);
+
+        ines.mapper = 0;
+        ines.misc_rom_chips = 2;
+        ines.misc_data = [vec![0x11; 0x1000], vec![0x22; 0x1000]].concat();
+        assert_eq!(ines.misc_roms(), vec![
+            MiscRom { kind: MiscRomKind::Unknown, data: vec![0x11; 0x1000] },
+            MiscRom { kind: MiscRomKind::Unknown, data: vec![0x22; 0x1000] },
+        ]);
+
+        ines.misc_rom_chips = 3;
+        assert_eq!(ines.misc_roms().len(), 1);
+    }
+
+    #[test]
+    fn repair_shrinks_truncated_sizes() {
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        rom.resize(16 + 20000, 0xEA);
+
+        let (mut ines, _) = Ines::from_rom_with(io::Cursor::new(&rom), ParseOptions { strict: false }).unwrap();
+        let report = ines.repair();
+        assert_eq!(report.changes, vec![
+            RepairChange::PaddedData { chip: RomChip::Prg, len: 0x8000 - 20000 },
+            RepairChange::RomSize { chip: RomChip::Chr, from: 0x2000, to: 0 },
+        ]);
+        assert!(ines.truncated.is_empty());
+        assert_eq!(ines.prg_data.len(), 0x8000);
+        assert!(ines.chr_data.is_empty());
+        assert_eq!(ines.to_bytes().unwrap()[4..6], [2, 0]);
+    }
+
+    #[test]
+    fn repair_keeps_exponent_sizes_below_a_bank() {
+        // nes 2.0 exponent-multiplier prg size of 2^12 * 3 = 12kb, 5000 bytes present
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 0x31, 0, 0, 0x08, 0, 0x0F, 0, 0, 0, 0, 0, 0];
+        rom.resize(16 + 5000, 0xEA);
+
+        let (mut ines, _) = Ines::from_rom_with(io::Cursor::new(&rom), ParseOptions { strict: false }).unwrap();
+        assert_eq!(ines.prg_rom_size, 12288);
+
+        let report = ines.repair();
+        assert_eq!(report.changes, vec![RepairChange::PaddedData { chip: RomChip::Prg, len: 12288 - 5000 }]);
+        assert_eq!(ines.prg_rom_size, 12288);
+        assert_eq!(ines.prg_data.len(), 12288);
+
+        let bytes = ines.to_bytes().unwrap();
+        assert_eq!((bytes[4], bytes[9]), (0x31, 0x0F));
+        assert_eq!(bytes.len(), 16 + 12288);
+    }
+
+    #[test]
+    fn repair_only_suggests_trailing_data_as_chr() {
+        // chr-ram cart followed by 8kb of trailing data
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        rom.resize(16 + 0x8000 + 0x2000, 0xEA);
+
+        let mut ines = Ines::from_rom(io::Cursor::new(&rom)).unwrap();
+        let report = ines.repair();
+        assert!(report.is_clean());
+        assert!(report.suggestions.is_empty());
+        assert!(ines.chr_data.is_empty());
+
+        // 8kb chr rom declared, another 8kb after it
+        rom[5] = 1;
+        rom.resize(16 + 0x8000 + 0x4000, 0xEA);
+
+        let mut ines = Ines::from_rom(io::Cursor::new(&rom)).unwrap();
+        let report = ines.repair();
+        assert!(report.is_clean());
+        assert_eq!(report.suggestions, vec![RepairChange::TrailingDataAsChr { len: 0x2000 }]);
+        assert_eq!(ines.chr_data.len(), 0x2000);
+    }
+}
@@ -12,9 +12,12 @@ extern crate lazy_static;
 pub mod unif;
 pub mod ines;
 pub mod fds;
+pub mod nsf;
 mod crc32;
+mod crc16;
 
 use std::io;
+use std::io::prelude::*;
 use std::error;
 use std::fmt;
 use std::convert::From;
@@ -22,37 +25,265 @@ use std::convert::From;
 static INES_GUARD: [u8; 4] = [0x4e, 0x45, 0x53, 0x1a];
 static UNIF_GUARD: [u8; 4] = [0x55, 0x4e, 0x49, 0x46];
 static FDS_GUARD: [u8; 4] = [0x46, 0x44, 0x53, 0x1a];
-//static NSF_GUARD: [u8; 5] = [0x4e, 0x45, 0x53, 0x4d, 0x1a];
+static NSF_GUARD: [u8; 5] = [0x4e, 0x45, 0x53, 0x4d, 0x1a];
+static NSFE_GUARD: [u8; 4] = [0x4e, 0x53, 0x46, 0x45];
+// block 1 of a headerless fds disk side, block code followed by "*NINTENDO-HVC*"
+static FDS_DISK_GUARD: [u8; 15] = [0x01, 0x2a, 0x4e, 0x49, 0x4e, 0x54, 0x45, 0x4e, 0x44, 0x4f, 0x2d, 0x48, 0x56, 0x43, 0x2a];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rom {
+    Ines(ines::Ines),
+    Unif(unif::Unif),
+    Fds(fds::Fds),
+}
+
+// nametable mirroring normalized across formats
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Mirroring {
+    Horizontal,
+    Vertical,
+    ScreenAOnly,
+    ScreenBOnly,
+    FourScreens,
+    MapperControlled,
+}
+
+// tv system normalized across formats
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Region {
+    NTSC,
+    PAL,
+    MultRegion,
+    Dendy,
+}
+
+/// Format independent view of a parsed rom
+pub trait RomInfo {
+    fn format(&self) -> RomFormat;
+    fn prg(&self) -> &[u8];
+    fn chr(&self) -> &[u8];
+    fn prg_crc(&self) -> u32;
+    fn chr_crc(&self) -> u32;
+    /// ines mapper number, unif boards are resolved through their nes 2.0 mapping
+    fn mapper(&self) -> Option<u32>;
+    fn submapper(&self) -> Option<u8>;
+    /// unif board name
+    fn board(&self) -> Option<&str>;
+    fn mirroring(&self) -> Mirroring;
+    fn region(&self) -> Region;
+    fn has_battery(&self) -> bool;
+}
+
+impl Rom {
+    fn info(&self) -> &dyn RomInfo {
+        match *self {
+            Rom::Ines(ref ines) => ines,
+            Rom::Unif(ref unif) => unif,
+            Rom::Fds(ref fds) => fds,
+        }
+    }
+}
+
+impl RomInfo for Rom {
+    fn format(&self) -> RomFormat { self.info().format() }
+    fn prg(&self) -> &[u8] { self.info().prg() }
+    fn chr(&self) -> &[u8] { self.info().chr() }
+    fn prg_crc(&self) -> u32 { self.info().prg_crc() }
+    fn chr_crc(&self) -> u32 { self.info().chr_crc() }
+    fn mapper(&self) -> Option<u32> { self.info().mapper() }
+    fn submapper(&self) -> Option<u8> { self.info().submapper() }
+    fn board(&self) -> Option<&str> { self.info().board() }
+    fn mirroring(&self) -> Mirroring { self.info().mirroring() }
+    fn region(&self) -> Region { self.info().region() }
+    fn has_battery(&self) -> bool { self.info().has_battery() }
+}
+
+/// Load and parse a rom file, the format is detected from the file contents
+/// 
+/// # Examples
+/// 
+///  ```no_run
+/// # let arg = std::fs::File::open("rom.nes").unwrap();
+/// match nes_rom::load(arg) {
+///     Ok(nes_rom::Rom::Ines(ines)) => println!("mapper: {}", ines.mapper),
+///     Ok(rom) => println!("{:?}", rom),
+///     Err(e) => println!("{}", e),
+/// }
+///  ```
+pub fn load<R: Read + Seek>(mut file: R) -> Result<Rom, RomError> {
+    let file_size = file.seek(io::SeekFrom::End(0))?;
+    file.seek(io::SeekFrom::Start(0))?;
+
+    let mut magic: [u8; 16] = [0; 16];
+    if file_size >= 16 {
+        file.read_exact(&mut magic)?;
+        file.seek(io::SeekFrom::Start(0))?;
+    }
+
+    if magic[0..4] == INES_GUARD {
+        return ines::Ines::from_rom(file).map(Rom::Ines);
+    }
+    if magic[0..4] == UNIF_GUARD {
+        return unif::Unif::from_rom(file).map(Rom::Unif);
+    }
+    // fds with or without the fwNES header
+    if magic[0..4] == FDS_GUARD || magic[0..15] == FDS_DISK_GUARD {
+        return fds::Fds::from_rom(file).map(Rom::Fds);
+    }
+
+    // headerless nes, only nrom prg + 8kb chr can be told apart from arbitrary data
+    if file_size == (16 + 8) * 1024 || file_size == (32 + 8) * 1024 {
+        let prg_banks = ((file_size - 8 * 1024) / (16 * 1024)) as u8;
+        let mut data = INES_GUARD.to_vec();
+        data.extend_from_slice(&[prg_banks, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        file.read_to_end(&mut data)?;
+        return ines::Ines::from_rom(io::Cursor::new(data)).map(Rom::Ines);
+    }
+
+    Err(RomError::new(ErrorKind::InvalidFormat).with_field("magic").with_offset(0)
+        .with_values("ines, unif or fds file", format!("{:02X?}", &magic[0..4])))
+}
+
+/// Parsing behaviour shared by the rom formats
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ParseOptions {
+    /// fail on any header or data violation, otherwise load anyway and report warnings
+    pub strict: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions { strict: true }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RomFormat {
+    Ines,
+    Unif,
+    Fds,
+    Nsf,
+}
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub enum RomError {
+pub enum ErrorKind {
     InvalidFormat,
     InvalidRom,
     InvalidConversion,
     IOError,
 }
 
-impl error::Error for RomError {
-    fn description(&self) -> &str {
-        match *self {
-            RomError::InvalidFormat => "invalid rom file format",
-            RomError::InvalidRom => "rom file contained invalid or corrupted data",
-            RomError::InvalidConversion => "unable to process conversion",
-            RomError::IOError => "rom file io error",
+/// Rom error with the context it occurred in, the format, file offset and field or chunk being parsed
+#[derive(Debug)]
+pub struct RomError {
+    kind: ErrorKind,
+    format: Option<RomFormat>,
+    offset: Option<u64>,
+    field: Option<String>,
+    expected: Option<String>,
+    found: Option<String>,
+    source: Option<io::Error>,
+}
+
+impl RomError {
+    pub fn new(kind: ErrorKind) -> RomError {
+        RomError {
+            kind,
+            format: None,
+            offset: None,
+            field: None,
+            expected: None,
+            found: None,
+            source: None,
         }
     }
+
+    pub fn with_format(mut self, format: RomFormat) -> RomError {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn with_offset(mut self, offset: u64) -> RomError {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn with_field<S: Into<String>>(mut self, field: S) -> RomError {
+        self.field = Some(field.into());
+        self
+    }
+
+    pub fn with_values<E: fmt::Display, F: fmt::Display>(mut self, expected: E, found: F) -> RomError {
+        self.expected = Some(expected.to_string());
+        self.found = Some(found.to_string());
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn format(&self) -> Option<RomFormat> {
+        self.format
+    }
+
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub fn expected(&self) -> Option<&str> {
+        self.expected.as_deref()
+    }
+
+    pub fn found(&self) -> Option<&str> {
+        self.found.as_deref()
+    }
+}
+
+impl error::Error for RomError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source.as_ref().map(|e| e as &(dyn error::Error + 'static))
+    }
 }
 
 impl fmt::Display for RomError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f,"{}", self)
+        let description = match self.kind {
+            ErrorKind::InvalidFormat => "invalid rom file format",
+            ErrorKind::InvalidRom => "rom file contained invalid or corrupted data",
+            ErrorKind::InvalidConversion => "unable to process conversion",
+            ErrorKind::IOError => "rom file io error",
+        };
+        write!(f, "{}", description)?;
+
+        if let Some(format) = self.format {
+            write!(f, " in {:?} rom", format)?;
+        }
+        if let Some(ref field) = self.field {
+            write!(f, " while parsing {}", field)?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at offset {:#X}", offset)?;
+        }
+        if let (Some(ref expected), Some(ref found)) = (&self.expected, &self.found) {
+            write!(f, ", expected {} found {}", expected, found)?;
+        }
+        if let Some(ref source) = self.source {
+            write!(f, ": {}", source)?;
+        }
+
+        Ok(())
     }
 }
 
 impl From<io::Error> for RomError {
-    fn from(_err: io::Error) -> Self {
-        RomError::IOError
+    fn from(err: io::Error) -> Self {
+        let mut e = RomError::new(ErrorKind::IOError);
+        e.source = Some(err);
+        e
     }
 }
-
-
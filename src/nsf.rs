@@ -0,0 +1,556 @@
+use std::io;
+use std::io::prelude::*;
+use super::{RomError,ErrorKind,RomFormat,Region};
+
+const HEADER_SIZE: u64 = 128;
+
+// expansion sound chips used by the tune
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct ExpansionAudio {
+    pub vrc6: bool,
+    pub vrc7: bool,
+    pub fds: bool,
+    pub mmc5: bool,
+    pub n163: bool,
+    pub sunsoft_5b: bool,
+    pub vt02: bool,
+}
+
+impl ExpansionAudio {
+    pub fn from_u8(flags: u8) -> ExpansionAudio {
+        ExpansionAudio {
+            vrc6: flags & 0x01 != 0,
+            vrc7: flags & 0x02 != 0,
+            fds: flags & 0x04 != 0,
+            mmc5: flags & 0x08 != 0,
+            n163: flags & 0x10 != 0,
+            sunsoft_5b: flags & 0x20 != 0,
+            vt02: flags & 0x40 != 0,
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        (self.vrc6 as u8)
+            | (self.vrc7 as u8) << 1
+            | (self.fds as u8) << 2
+            | (self.mmc5 as u8) << 3
+            | (self.n163 as u8) << 4
+            | (self.sunsoft_5b as u8) << 5
+            | (self.vt02 as u8) << 6
+    }
+}
+
+// per track metadata from the nsfe or nsf2 chunks
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TrackInfo {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    // milliseconds
+    pub duration: Option<u32>,
+    pub fade: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nsf {
+    // 0 for nsfe files
+    pub version: u8,
+    pub total_songs: u8,
+    // 1 based
+    pub starting_song: u8,
+    pub load_address: u16,
+    pub init_address: u16,
+    pub play_address: u16,
+    pub name: String,
+    pub artist: String,
+    pub copyright: String,
+    // play routine call period in microseconds
+    pub ntsc_speed: u16,
+    pub pal_speed: u16,
+    // initial banks of $8000-$FFFF, all zero if the tune is not bankswitched
+    pub bankswitch_init: [u8; 8],
+    pub region: Region,
+    pub expansion_audio: ExpansionAudio,
+    // nsf2 feature flags, reserved in version 1
+    pub nsf2_flags: u8,
+    pub data: Vec<u8>,
+    // metadata of the nsfe or nsf2 chunks
+    pub ripper: Option<String>,
+    // one entry per song
+    pub tracks: Vec<TrackInfo>,
+    pub playlist: Option<Vec<u8>>,
+    pub sound_effects: Option<Vec<u8>>,
+    pub text: Option<String>,
+}
+
+impl Nsf {
+    pub fn new() -> Nsf {
+        Nsf {
+            version: 1,
+            total_songs: 1,
+            starting_song: 1,
+            load_address: 0x8000,
+            init_address: 0x8000,
+            play_address: 0x8000,
+            name: String::new(),
+            artist: String::new(),
+            copyright: String::new(),
+            ntsc_speed: 16639,
+            pal_speed: 19997,
+            bankswitch_init: [0; 8],
+            region: Region::NTSC,
+            expansion_audio: ExpansionAudio::default(),
+            nsf2_flags: 0,
+            data: Vec::new(),
+            ripper: None,
+            tracks: Vec::new(),
+            playlist: None,
+            sound_effects: None,
+            text: None,
+        }
+    }
+
+    /// Load and parse .nsf or .nsfe file, including nsf2 metadata
+    ///
+    /// # Examples
+    ///
+    ///  ```no_run
+    /// # let arg = std::fs::File::open("music.nsf").unwrap();
+    /// let nsf = nes_rom::nsf::Nsf::from_rom(arg);
+    ///  ```
+    pub fn from_rom<R: Read + Seek>(mut file: R) -> Result<Nsf, RomError> {
+        let mut magic: [u8; 4] = [0; 4];
+        file.read_exact(&mut magic).map_err(|e| RomError::from(e).with_format(RomFormat::Nsf).with_field("header").with_offset(0))?;
+        file.seek(io::SeekFrom::Start(0))?;
+        if magic == super::NSFE_GUARD {
+            return Nsf::from_nsfe(file);
+        }
+
+        let mut header: [u8; HEADER_SIZE as usize] = [0; HEADER_SIZE as usize];
+        file.read_exact(&mut header).map_err(|e| RomError::from(e).with_format(RomFormat::Nsf).with_field("header").with_offset(0))?;
+        // check header
+        if header[0..5] != super::NSF_GUARD {
+            return Err(nsf_error(ErrorKind::InvalidFormat, "magic").with_offset(0)
+                .with_values(format!("{:02X?} or {:02X?}", super::NSF_GUARD, super::NSFE_GUARD), format!("{:02X?}", &header[0..5])));
+        }
+
+        let mut nsf = Nsf::new();
+        nsf.version = header[5];
+        nsf.total_songs = header[6];
+        nsf.starting_song = header[7];
+        nsf.load_address = u16::from_le_bytes([header[8], header[9]]);
+        nsf.init_address = u16::from_le_bytes([header[10], header[11]]);
+        nsf.play_address = u16::from_le_bytes([header[12], header[13]]);
+        nsf.name = header_string(&header[0x0E..0x2E]);
+        nsf.artist = header_string(&header[0x2E..0x4E]);
+        nsf.copyright = header_string(&header[0x4E..0x6E]);
+        nsf.ntsc_speed = u16::from_le_bytes([header[0x6E], header[0x6F]]);
+        nsf.bankswitch_init.copy_from_slice(&header[0x70..0x78]);
+        nsf.pal_speed = u16::from_le_bytes([header[0x78], header[0x79]]);
+        nsf.region = match header[0x7A] & 0x03 {
+            0 => Region::NTSC,
+            1 => Region::PAL,
+            _ => Region::MultRegion,
+        };
+        nsf.expansion_audio = ExpansionAudio::from_u8(header[0x7B]);
+        nsf.nsf2_flags = header[0x7C];
+
+        if nsf.total_songs == 0 {
+            return Err(nsf_error(ErrorKind::InvalidRom, "total songs").with_offset(6).with_values("at least 1 song", 0));
+        }
+
+        let file_size = file.seek(io::SeekFrom::End(0))?;
+        file.seek(io::SeekFrom::Start(HEADER_SIZE))?;
+
+        // nsf2 stores the program length when metadata follows the program, zero means up to the end of file
+        let data_len = u32::from_le_bytes([header[0x7D], header[0x7E], header[0x7F], 0]) as u64;
+        let data_len = if nsf.version >= 2 && data_len != 0 {
+            if HEADER_SIZE + data_len > file_size {
+                return Err(nsf_error(ErrorKind::InvalidRom, "program data").with_offset(HEADER_SIZE)
+                    .with_values(format!("{} bytes", data_len), format!("{} bytes", file_size - HEADER_SIZE)));
+            }
+            data_len
+        }
+        else {
+            file_size - HEADER_SIZE
+        };
+
+        nsf.data = vec![0; data_len as usize];
+        file.read_exact(&mut nsf.data).map_err(|e| RomError::from(e).with_format(RomFormat::Nsf).with_field("program data").with_offset(HEADER_SIZE))?;
+        if nsf.data.is_empty() {
+            return Err(nsf_error(ErrorKind::InvalidRom, "program data").with_offset(HEADER_SIZE).with_values("program data", "none"));
+        }
+
+        // nsf2 metadata chunks
+        if HEADER_SIZE + data_len < file_size {
+            let mut chunks = Chunks::Nsf2;
+            while read_chunk(&mut file, &mut nsf, &mut chunks, file_size)? {
+
+            }
+        }
+
+        nsf.tracks.resize(nsf.total_songs as usize, TrackInfo::default());
+        Ok(nsf)
+    }
+
+    fn from_nsfe<R: Read + Seek>(mut file: R) -> Result<Nsf, RomError> {
+        let file_size = file.seek(io::SeekFrom::End(0))?;
+        file.seek(io::SeekFrom::Start(super::NSFE_GUARD.len() as u64))?;
+
+        let mut nsf = Nsf::new();
+        nsf.version = 0;
+        let mut chunks = Chunks::Nsfe { info: false, data: false };
+        while read_chunk(&mut file, &mut nsf, &mut chunks, file_size)? {
+
+        }
+
+        if let Chunks::Nsfe { info, data } = chunks {
+            if !info {
+                return Err(nsf_error(ErrorKind::InvalidRom, "INFO chunk").with_values("load and init addresses", "missing chunk"));
+            }
+            if !data {
+                return Err(nsf_error(ErrorKind::InvalidRom, "DATA chunk").with_values("program data", "missing chunk"));
+            }
+        }
+
+        nsf.tracks.resize(nsf.total_songs as usize, TrackInfo::default());
+        Ok(nsf)
+    }
+
+    /// true if the tune uses the $5FF8-$5FFF bank registers
+    pub fn is_bankswitched(&self) -> bool {
+        self.bankswitch_init.iter().any(|&bank| bank != 0)
+    }
+}
+
+impl Default for Nsf {
+    fn default() -> Self {
+        Nsf::new()
+    }
+}
+
+// chunks allowed in the file and the required ones found so far
+enum Chunks {
+    Nsfe { info: bool, data: bool },
+    // INFO, DATA and BANK are taken from the header
+    Nsf2,
+}
+
+fn read_chunk<R: Read + Seek>(file: &mut R, nsf: &mut Nsf, chunks: &mut Chunks, end_of_file: u64) -> Result<bool, RomError> {
+    let offset = file.stream_position()?;
+    // nsfe chunks store the length in front of the type
+    let mut len_buf = [0u8; 4];
+    let mut type_buf = [0u8; 4];
+    file.read_exact(&mut len_buf).map_err(|e| RomError::from(e).with_format(RomFormat::Nsf).with_field("chunk header").with_offset(offset))?;
+    file.read_exact(&mut type_buf).map_err(|e| RomError::from(e).with_format(RomFormat::Nsf).with_field("chunk header").with_offset(offset))?;
+
+    let chunk_field = format!("{} chunk", String::from_utf8_lossy(&type_buf));
+    let len = u32::from_le_bytes(len_buf) as u64;
+    if offset + 8 + len > end_of_file {
+        return Err(nsf_error(ErrorKind::InvalidRom, chunk_field).with_offset(offset)
+            .with_values(format!("{} bytes", len), format!("{} bytes", end_of_file - offset - 8)));
+    }
+    let mut chunk_buf = vec![0u8; len as usize];
+    file.read_exact(&mut chunk_buf).map_err(|e| RomError::from(e).with_format(RomFormat::Nsf).with_field(chunk_field.as_str()).with_offset(offset))?;
+
+    let nsf2 = matches!(chunks, Chunks::Nsf2);
+    match &type_buf {
+        b"INFO" | b"DATA" | b"BANK" if nsf2 => (),
+        b"INFO" => {
+            if chunk_buf.len() < 8 {
+                return Err(nsf_error(ErrorKind::InvalidRom, chunk_field).with_offset(offset).with_values("at least 8 bytes", chunk_buf.len()));
+            }
+            nsf.load_address = u16::from_le_bytes([chunk_buf[0], chunk_buf[1]]);
+            nsf.init_address = u16::from_le_bytes([chunk_buf[2], chunk_buf[3]]);
+            nsf.play_address = u16::from_le_bytes([chunk_buf[4], chunk_buf[5]]);
+            nsf.region = match chunk_buf[6] & 0x03 {
+                0 => Region::NTSC,
+                1 => Region::PAL,
+                _ => Region::MultRegion,
+            };
+            nsf.expansion_audio = ExpansionAudio::from_u8(chunk_buf[7]);
+            nsf.total_songs = chunk_buf.get(8).copied().unwrap_or(1);
+            // nsfe counts songs from 0
+            nsf.starting_song = chunk_buf.get(9).copied().unwrap_or(0).wrapping_add(1);
+            if let Chunks::Nsfe { ref mut info, .. } = *chunks {
+                *info = true;
+            }
+        },
+        b"DATA" => {
+            nsf.data = chunk_buf;
+            if let Chunks::Nsfe { ref mut data, .. } = *chunks {
+                *data = true;
+            }
+        },
+        b"BANK" => {
+            let len = chunk_buf.len().min(8);
+            nsf.bankswitch_init[..len].copy_from_slice(&chunk_buf[..len]);
+        },
+        b"RATE" => {
+            if chunk_buf.len() >= 2 {
+                nsf.ntsc_speed = u16::from_le_bytes([chunk_buf[0], chunk_buf[1]]);
+            }
+            if chunk_buf.len() >= 4 {
+                nsf.pal_speed = u16::from_le_bytes([chunk_buf[2], chunk_buf[3]]);
+            }
+        },
+        b"NEND" => return Ok(false),
+        b"auth" => {
+            let mut strings = chunk_strings(&chunk_buf).into_iter();
+            nsf.name = strings.next().unwrap_or_default();
+            nsf.artist = strings.next().unwrap_or_default();
+            nsf.copyright = strings.next().unwrap_or_default();
+            nsf.ripper = strings.next();
+        },
+        b"tlbl" => {
+            for (track, title) in track_entries(&mut nsf.tracks, chunk_strings(&chunk_buf)) {
+                track.title = Some(title);
+            }
+        },
+        b"taut" => {
+            for (track, author) in track_entries(&mut nsf.tracks, chunk_strings(&chunk_buf)) {
+                track.author = Some(author);
+            }
+        },
+        b"time" => {
+            for (track, ms) in track_entries(&mut nsf.tracks, chunk_times(&chunk_buf)) {
+                track.duration = ms;
+            }
+        },
+        b"fade" => {
+            for (track, ms) in track_entries(&mut nsf.tracks, chunk_times(&chunk_buf)) {
+                track.fade = ms;
+            }
+        },
+        b"plst" => nsf.playlist = Some(chunk_buf),
+        b"psfx" => nsf.sound_effects = Some(chunk_buf),
+        b"text" => nsf.text = Some(chunk_strings(&chunk_buf).join("\n")),
+        // chunks starting with an upper case letter have to be understood to play the file,
+        // nsf2 metadata may be skipped unless bit 7 of the feature flags marks it as mandatory
+        _ if type_buf[0].is_ascii_uppercase() && (!nsf2 || nsf.nsf2_flags & 0x80 != 0) => {
+            return Err(nsf_error(ErrorKind::InvalidFormat, chunk_field).with_offset(offset).with_values("known required chunk", "unsupported chunk"));
+        },
+        _ => (),
+    }
+
+    Ok(file.stream_position()? < end_of_file)
+}
+
+// grows the track list to fit every entry of a per track chunk
+fn track_entries<T>(tracks: &mut Vec<TrackInfo>, entries: Vec<T>) -> impl Iterator<Item = (&mut TrackInfo, T)> {
+    if tracks.len() < entries.len() {
+        tracks.resize(entries.len(), TrackInfo::default());
+    }
+    tracks.iter_mut().zip(entries)
+}
+
+// null terminated strings
+fn chunk_strings(buf: &[u8]) -> Vec<String> {
+    let buf = buf.strip_suffix(&[0]).unwrap_or(buf);
+    if buf.is_empty() {
+        return Vec::new();
+    }
+    buf.split(|&b| b == 0).map(|s| String::from_utf8_lossy(s).into_owned()).collect()
+}
+
+// signed milliseconds, negative values select the player default
+fn chunk_times(buf: &[u8]) -> Vec<Option<u32>> {
+    buf.chunks_exact(4)
+        .map(|ms| i32::from_le_bytes([ms[0], ms[1], ms[2], ms[3]]))
+        .map(|ms| if ms < 0 { None } else { Some(ms as u32) })
+        .collect()
+}
+
+// fixed size null terminated header strings, the encoding is unspecified
+fn header_string(buf: &[u8]) -> String {
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+fn nsf_error<S: Into<String>>(kind: ErrorKind, field: S) -> RomError {
+    RomError::new(kind).with_format(RomFormat::Nsf).with_field(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // version 1 header of a 3 song tune
+    fn nsf_header() -> Vec<u8> {
+        let mut header = vec![0; HEADER_SIZE as usize];
+        header[0..5].copy_from_slice(&crate::NSF_GUARD);
+        header[5] = 1;
+        header[6] = 3;
+        header[7] = 2;
+        header[8..14].copy_from_slice(&[0x00, 0x80, 0x03, 0x80, 0x06, 0x80]);
+        header[0x0E..0x13].copy_from_slice(b"Title");
+        header[0x2E..0x34].copy_from_slice(b"Artist");
+        header[0x4E..0x52].copy_from_slice(b"1986");
+        header[0x6E..0x70].copy_from_slice(&16639u16.to_le_bytes());
+        header[0x78..0x7A].copy_from_slice(&19997u16.to_le_bytes());
+        header
+    }
+
+    #[test]
+    fn header_fields() {
+        let mut rom = nsf_header();
+        rom[0x70..0x78].copy_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7]);
+        rom.extend_from_slice(&[0xEA; 0x100]);
+
+        let nsf = Nsf::from_rom(io::Cursor::new(rom)).unwrap();
+        assert_eq!(nsf.version, 1);
+        assert_eq!(nsf.total_songs, 3);
+        assert_eq!(nsf.starting_song, 2);
+        assert_eq!((nsf.load_address, nsf.init_address, nsf.play_address), (0x8000, 0x8003, 0x8006));
+        assert_eq!((nsf.name.as_str(), nsf.artist.as_str(), nsf.copyright.as_str()), ("Title", "Artist", "1986"));
+        assert_eq!((nsf.ntsc_speed, nsf.pal_speed), (16639, 19997));
+        assert_eq!(nsf.bankswitch_init, [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert!(nsf.is_bankswitched());
+        assert_eq!(nsf.region, Region::NTSC);
+        assert_eq!(nsf.expansion_audio, ExpansionAudio::default());
+        assert_eq!(nsf.data, vec![0xEA; 0x100]);
+        assert_eq!(nsf.tracks, vec![TrackInfo::default(); 3]);
+    }
+
+    #[test]
+    fn region_and_expansion_audio() {
+        let regions = [(0, Region::NTSC), (1, Region::PAL), (2, Region::MultRegion), (3, Region::MultRegion)];
+        for (flags, region) in regions.iter() {
+            let mut rom = nsf_header();
+            rom[0x7A] = *flags;
+            rom.push(0x60);
+            assert_eq!(Nsf::from_rom(io::Cursor::new(rom)).unwrap().region, *region);
+        }
+
+        let mut rom = nsf_header();
+        rom[0x7B] = 0b0010_0101;
+        rom.push(0x60);
+        let audio = Nsf::from_rom(io::Cursor::new(rom)).unwrap().expansion_audio;
+        assert_eq!(audio, ExpansionAudio { vrc6: true, fds: true, sunsoft_5b: true, ..ExpansionAudio::default() });
+        assert_eq!(audio.to_u8(), 0b0010_0101);
+        assert_eq!(ExpansionAudio::from_u8(0x7F).to_u8(), 0x7F);
+    }
+
+    fn chunk(chunk_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut bytes = (data.len() as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(chunk_type);
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn times(ms: &[i32]) -> Vec<u8> {
+        ms.iter().flat_map(|ms| ms.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn nsfe_chunks() {
+        let mut rom = crate::NSFE_GUARD.to_vec();
+        // load, init, play, pal, vrc6 + n163, 3 songs, starting song 0 based
+        rom.extend(chunk(b"INFO", &[0x00, 0x80, 0x03, 0x80, 0x06, 0x80, 0x01, 0x11, 3, 1]));
+        rom.extend(chunk(b"DATA", &[0xEA; 0x20]));
+        rom.extend(chunk(b"BANK", &[0, 1, 2]));
+        rom.extend(chunk(b"RATE", &[0x10, 0x27, 0x20, 0x4E]));
+        rom.extend(chunk(b"auth", b"Game\0Artist\0Copyright\0Ripper\0"));
+        rom.extend(chunk(b"tlbl", b"One\0Two\0Three\0"));
+        rom.extend(chunk(b"time", &times(&[90000, -1, 30000])));
+        rom.extend(chunk(b"fade", &times(&[5000, 0])));
+        rom.extend(chunk(b"plst", &[2, 0, 1]));
+        rom.extend(chunk(b"NEND", &[]));
+
+        let nsf = Nsf::from_rom(io::Cursor::new(rom)).unwrap();
+        assert_eq!(nsf.version, 0);
+        assert_eq!((nsf.load_address, nsf.init_address, nsf.play_address), (0x8000, 0x8003, 0x8006));
+        assert_eq!(nsf.region, Region::PAL);
+        assert_eq!(nsf.expansion_audio, ExpansionAudio { vrc6: true, n163: true, ..ExpansionAudio::default() });
+        assert_eq!((nsf.total_songs, nsf.starting_song), (3, 2));
+        assert_eq!(nsf.data, vec![0xEA; 0x20]);
+        assert_eq!(nsf.bankswitch_init, [0, 1, 2, 0, 0, 0, 0, 0]);
+        assert_eq!((nsf.ntsc_speed, nsf.pal_speed), (10000, 20000));
+        assert_eq!((nsf.name.as_str(), nsf.artist.as_str(), nsf.copyright.as_str()), ("Game", "Artist", "Copyright"));
+        assert_eq!(nsf.ripper.as_deref(), Some("Ripper"));
+        assert_eq!(nsf.playlist, Some(vec![2, 0, 1]));
+
+        let titles: Vec<Option<&str>> = nsf.tracks.iter().map(|t| t.title.as_deref()).collect();
+        assert_eq!(titles, vec![Some("One"), Some("Two"), Some("Three")]);
+        // negative times select the player default
+        let durations: Vec<Option<u32>> = nsf.tracks.iter().map(|t| t.duration).collect();
+        assert_eq!(durations, vec![Some(90000), None, Some(30000)]);
+        let fades: Vec<Option<u32>> = nsf.tracks.iter().map(|t| t.fade).collect();
+        assert_eq!(fades, vec![Some(5000), Some(0), None]);
+    }
+
+    #[test]
+    fn nsfe_required_chunks() {
+        let mut rom = crate::NSFE_GUARD.to_vec();
+        rom.extend(chunk(b"INFO", &[0x00, 0x80, 0x03, 0x80, 0x06, 0x80, 0, 0]));
+        let e = Nsf::from_rom(io::Cursor::new(&rom)).unwrap_err();
+        assert_eq!(e.field(), Some("DATA chunk"));
+
+        rom.extend(chunk(b"DATA", &[0x60]));
+        let nsf = Nsf::from_rom(io::Cursor::new(&rom)).unwrap();
+        assert_eq!((nsf.total_songs, nsf.starting_song), (1, 1));
+
+        // unknown upper case chunks can't be skipped in nsfe
+        rom.extend(chunk(b"ZZZZ", &[0]));
+        let e = Nsf::from_rom(io::Cursor::new(&rom)).unwrap_err();
+        assert_eq!((e.kind(), e.field()), (ErrorKind::InvalidFormat, Some("ZZZZ chunk")));
+    }
+
+    #[test]
+    fn nsf2_metadata_chunks() {
+        let mut rom = nsf_header();
+        rom[5] = 2;
+        rom[0x7D] = 0x10;
+        rom.extend_from_slice(&[0xEA; 0x10]);
+        // header fields win over INFO, DATA and BANK chunks
+        rom.extend(chunk(b"INFO", &[0x00, 0x90, 0x00, 0x90, 0x00, 0x90, 0x01, 0x00, 9, 0]));
+        rom.extend(chunk(b"DATA", &[0x60]));
+        rom.extend(chunk(b"tlbl", b"One\0Two\0"));
+        rom.extend(chunk(b"ZZZZ", &[0]));
+
+        let nsf = Nsf::from_rom(io::Cursor::new(&rom)).unwrap();
+        assert_eq!(nsf.data, vec![0xEA; 0x10]);
+        assert_eq!((nsf.load_address, nsf.total_songs, nsf.region), (0x8000, 3, Region::NTSC));
+        let titles: Vec<Option<&str>> = nsf.tracks.iter().map(|t| t.title.as_deref()).collect();
+        assert_eq!(titles, vec![Some("One"), Some("Two"), None]);
+
+        // unknown upper case chunks are only an error when the metadata is mandatory
+        rom[0x7C] = 0x80;
+        let e = Nsf::from_rom(io::Cursor::new(&rom)).unwrap_err();
+        assert_eq!((e.kind(), e.field()), (ErrorKind::InvalidFormat, Some("ZZZZ chunk")));
+
+        // program length past the end of file
+        rom[0x7F] = 0x01;
+        let e = Nsf::from_rom(io::Cursor::new(&rom)).unwrap_err();
+        assert_eq!((e.field(), e.offset()), (Some("program data"), Some(HEADER_SIZE)));
+    }
+
+    #[test]
+    fn nsf2_without_metadata() {
+        // zero program length, everything after the header is program data
+        let mut rom = nsf_header();
+        rom[5] = 2;
+        rom.extend_from_slice(&chunk(b"tlbl", b"One\0"));
+
+        let nsf = Nsf::from_rom(io::Cursor::new(&rom)).unwrap();
+        assert_eq!(nsf.data, rom[HEADER_SIZE as usize..]);
+        assert_eq!(nsf.tracks, vec![TrackInfo::default(); 3]);
+    }
+
+    #[test]
+    fn invalid_files() {
+        let mut rom = nsf_header();
+        rom[6] = 0;
+        rom.push(0x60);
+        let e = Nsf::from_rom(io::Cursor::new(rom)).unwrap_err();
+        assert_eq!((e.kind(), e.field(), e.offset()), (ErrorKind::InvalidRom, Some("total songs"), Some(6)));
+
+        let e = Nsf::from_rom(io::Cursor::new(nsf_header())).unwrap_err();
+        assert_eq!((e.kind(), e.field(), e.offset()), (ErrorKind::InvalidRom, Some("program data"), Some(HEADER_SIZE)));
+
+        let mut rom = nsf_header();
+        rom[3] = b'S';
+        rom.push(0x60);
+        let e = Nsf::from_rom(io::Cursor::new(rom)).unwrap_err();
+        assert_eq!((e.kind(), e.field()), (ErrorKind::InvalidFormat, Some("magic")));
+    }
+}
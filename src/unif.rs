@@ -4,262 +4,294 @@ use std::io::prelude::*;
 use std::collections::HashMap;
 use num_derive::FromPrimitive;    
 use num_traits::FromPrimitive;
-use super::{RomError,ines,crc32};
+use super::{RomError,ErrorKind,RomFormat,RomInfo,Mirroring,Region,ines,crc32};
 
 lazy_static! {
     // based on https://wiki.nesdev.com/w/index.php/UNIF_to_NES_2.0_Mapping
-    // mapper, submapper, prg ram, chr ram
-    static ref UNIF_BOARD_MAPPINGS: HashMap<&'static str, (u32, u8, u32, u32)> = {
+    // mapper, submapper, prg ram, chr ram, prefix written in front of the board name
+    static ref UNIF_BOARD_MAPPINGS: HashMap<&'static str, (u32, u8, u32, u32, &'static str)> = {
         let mut map = HashMap::new();
         // nintendo-made boards
-        map.insert("B4", (4,0,0,0));
-        map.insert("AMROM", (7,2,0,8));
-        map.insert("ANROM", (7,1,0,8));
-        map.insert("AN1ROM", (7,1,0,8));
-        map.insert("AOROM", (7,0,0,8));
-        map.insert("BNROM", (34,2,0,8));
-        map.insert("CNROM", (3,2,0,0));
-        map.insert("CNROM+SECURITY", (185,0,0,0));
-        map.insert("CPROM", (13,0,0,16));
-        map.insert("FAMILYBASIC", (0,0,2,0));
-        map.insert("EKROM", (5,0,8,0));
-        map.insert("ELROM", (5,0,0,0));
-        map.insert("ETROM", (5,0,16,8)); //8kb prg-ram+8kb prg-nvram
-        map.insert("EWROM", (5,0,32,0));
-        map.insert("FJROM", (10,0,8,0));
-        map.insert("FKROM", (10,0,8,0));
-        map.insert("HROM", (0,0,0,0));
-        map.insert("HKROM", (4,1,1,0));
-        map.insert("NROM", (0,0,0,0));
-        map.insert("NROM-128", (0,0,0,0));
-        map.insert("NROM-256", (0,0,0,0));
-        map.insert("PEEOROM", (9,0,0,0));
-        map.insert("PNROM", (9,0,0,0));
-        map.insert("RROM", (0,0,0,0));
-        map.insert("RTROM", (0,0,0,0));
-        map.insert("SROM", (0,0,0,0));
-        map.insert("SAROM", (1,0,0,0));
-        map.insert("SBROM", (1,0,0,0));
-        map.insert("SCROM", (1,0,0,0));
-        map.insert("SC1ROM", (1,0,0,0));
-        map.insert("SEROM", (1,5,0,0));
-        map.insert("SFROM", (1,0,0,0));
-        map.insert("SF1ROM", (1,0,0,0));
-        map.insert("SFEXPROM", (1,0,0,0));
-        map.insert("SGROM", (1,0,0,0));
-        map.insert("SHROM", (1,5,0,0));
-        map.insert("SH1ROM", (1,5,0,0));
-        map.insert("SIROM", (1,0,8,0));
-        map.insert("SJROM", (1,0,8,0));
-        map.insert("SKROM", (1,0,8,0));
-        map.insert("SLROM", (1,0,0,0));
-        map.insert("SL1ROM", (1,0,0,0));
-        map.insert("SL2ROM", (1,0,0,0));
-        map.insert("SL3ROM", (1,0,0,0));
-        map.insert("SLRROM", (1,0,0,0));
-        map.insert("SMROM", (1,0,0,8));
-        map.insert("SNROM", (1,0,8,8));
-        map.insert("SNWEPROM", (1,0,8,8));
-        map.insert("SOROM", (1,0,16,8)); //8kb prg-ram+8kb prg-nvram
-        map.insert("SUROM", (1,0,8,8));
-        map.insert("SXROM", (1,0,32,8));
-        map.insert("TBROM", (4,0,0,0));
-        map.insert("TEROM", (4,0,0,0));
-        map.insert("TFROM", (4,0,0,0));
-        map.insert("TGROM", (4,0,0,8));
-        map.insert("TKROM", (4,0,8,0));
-        map.insert("TK1ROM", (4,0,8,0));
-        map.insert("TKEPROM", (4,0,8,0));
-        map.insert("TKSROM", (118,0,8,0));
-        map.insert("TLROM", (4,0,0,0));
-        map.insert("TL1ROM", (4,0,0,0));
-        map.insert("TL2ROM", (4,0,0,0));
-        map.insert("TLSROM", (118,0,0,0));
-        map.insert("TNROM", (4,0,8,8));
-        map.insert("TQROM", (119,0,0,8));
-        map.insert("TR1ROM", (4,0,0,0));
-        map.insert("TSROM", (4,0,8,0));
-        map.insert("TVROM", (4,0,0,0));
-        map.insert("STROM", (0,0,0,0));
-        map.insert("UNROM", (2,2,0,8));
-        map.insert("UOROM", (2,2,0,8));
+        map.insert("B4", (4,0,0,0,"NES-"));
+        map.insert("AMROM", (7,2,0,8,"NES-"));
+        map.insert("ANROM", (7,1,0,8,"NES-"));
+        map.insert("AN1ROM", (7,1,0,8,"NES-"));
+        map.insert("AOROM", (7,0,0,8,"NES-"));
+        map.insert("BNROM", (34,2,0,8,"NES-"));
+        map.insert("CNROM", (3,2,0,0,"NES-"));
+        map.insert("CNROM+SECURITY", (185,0,0,0,"NES-"));
+        map.insert("CPROM", (13,0,0,16,"NES-"));
+        map.insert("FAMILYBASIC", (0,0,2,0,"NES-"));
+        map.insert("EKROM", (5,0,8,0,"NES-"));
+        map.insert("ELROM", (5,0,0,0,"NES-"));
+        map.insert("ETROM", (5,0,16,8,"NES-")); //8kb prg-ram+8kb prg-nvram
+        map.insert("EWROM", (5,0,32,0,"NES-"));
+        map.insert("FJROM", (10,0,8,0,"NES-"));
+        map.insert("FKROM", (10,0,8,0,"NES-"));
+        map.insert("HROM", (0,0,0,0,"NES-"));
+        map.insert("HKROM", (4,1,1,0,"NES-"));
+        map.insert("NROM", (0,0,0,0,"NES-"));
+        map.insert("NROM-128", (0,0,0,0,"NES-"));
+        map.insert("NROM-256", (0,0,0,0,"NES-"));
+        map.insert("PEEOROM", (9,0,0,0,"NES-"));
+        map.insert("PNROM", (9,0,0,0,"NES-"));
+        map.insert("RROM", (0,0,0,0,"NES-"));
+        map.insert("RTROM", (0,0,0,0,"NES-"));
+        map.insert("SROM", (0,0,0,0,"NES-"));
+        map.insert("SAROM", (1,0,0,0,"NES-"));
+        map.insert("SBROM", (1,0,0,0,"NES-"));
+        map.insert("SCROM", (1,0,0,0,"NES-"));
+        map.insert("SC1ROM", (1,0,0,0,"NES-"));
+        map.insert("SEROM", (1,5,0,0,"NES-"));
+        map.insert("SFROM", (1,0,0,0,"NES-"));
+        map.insert("SF1ROM", (1,0,0,0,"NES-"));
+        map.insert("SFEXPROM", (1,0,0,0,"NES-"));
+        map.insert("SGROM", (1,0,0,0,"NES-"));
+        map.insert("SHROM", (1,5,0,0,"NES-"));
+        map.insert("SH1ROM", (1,5,0,0,"NES-"));
+        map.insert("SIROM", (1,0,8,0,"NES-"));
+        map.insert("SJROM", (1,0,8,0,"NES-"));
+        map.insert("SKROM", (1,0,8,0,"NES-"));
+        map.insert("SLROM", (1,0,0,0,"NES-"));
+        map.insert("SL1ROM", (1,0,0,0,"NES-"));
+        map.insert("SL2ROM", (1,0,0,0,"NES-"));
+        map.insert("SL3ROM", (1,0,0,0,"NES-"));
+        map.insert("SLRROM", (1,0,0,0,"NES-"));
+        map.insert("SMROM", (1,0,0,8,"NES-"));
+        map.insert("SNROM", (1,0,8,8,"NES-"));
+        map.insert("SNWEPROM", (1,0,8,8,"NES-"));
+        map.insert("SOROM", (1,0,16,8,"NES-")); //8kb prg-ram+8kb prg-nvram
+        map.insert("SUROM", (1,0,8,8,"NES-"));
+        map.insert("SXROM", (1,0,32,8,"NES-"));
+        map.insert("TBROM", (4,0,0,0,"NES-"));
+        map.insert("TEROM", (4,0,0,0,"NES-"));
+        map.insert("TFROM", (4,0,0,0,"NES-"));
+        map.insert("TGROM", (4,0,0,8,"NES-"));
+        map.insert("TKROM", (4,0,8,0,"NES-"));
+        map.insert("TK1ROM", (4,0,8,0,"NES-"));
+        map.insert("TKEPROM", (4,0,8,0,"NES-"));
+        map.insert("TKSROM", (118,0,8,0,"NES-"));
+        map.insert("TLROM", (4,0,0,0,"NES-"));
+        map.insert("TL1ROM", (4,0,0,0,"NES-"));
+        map.insert("TL2ROM", (4,0,0,0,"NES-"));
+        map.insert("TLSROM", (118,0,0,0,"NES-"));
+        map.insert("TNROM", (4,0,8,8,"NES-"));
+        map.insert("TQROM", (119,0,0,8,"NES-"));
+        map.insert("TR1ROM", (4,0,0,0,"NES-"));
+        map.insert("TSROM", (4,0,8,0,"NES-"));
+        map.insert("TVROM", (4,0,0,0,"NES-"));
+        map.insert("STROM", (0,0,0,0,"NES-"));
+        map.insert("UNROM", (2,2,0,8,"NES-"));
+        map.insert("UOROM", (2,2,0,8,"NES-"));
         // boards made by third-party licensees
-        map.insert("ACCLAIM-MC-ACC", (4,3,0,0));
-        map.insert("BANDAI-FCG-1", (16,0,0,0));
-        map.insert("BANDAI-FCG-2", (16,0,0,0));
-        map.insert("BANDAI-LZ93D50", (16,0,0,0));
-        map.insert("BANDAI-LZ93D50+24C01", (159,0,128,0));
-        map.insert("BANDAI-LZ93D50+24C02", (16,0,256,0));
-        map.insert("BANDAI-PT-554", (3,2,0,0));
-        map.insert("IREM-FCG-1", (16,0,0,0));
-        map.insert("JALECO-JF01", (0,0,0,0));
-        map.insert("JALECO-JF02", (0,0,0,0));
-        map.insert("JALECO-JF03", (0,0,0,0));
-        map.insert("JALECO-JF04", (0,0,0,0));
-        map.insert("JALECO-JF15", (2,2,0,8));
-        map.insert("JALECO-JF18", (2,2,0,8));
-        map.insert("JALECO-JF23", (18,0,0,0));
-        map.insert("JALECO-JF24", (18,0,0,0));
-        map.insert("JALECO-JF25", (18,0,0,0));
-        map.insert("JALECO-JF27", (18,0,8,0));
-        map.insert("JALECO-JF29", (18,0,0,0));
-        map.insert("JALECO-JF37", (18,0,0,0));
-        map.insert("JALECO-JF40", (18,0,8,0));
-        map.insert("NAMCOT-129", (19,0,0,0));
-        map.insert("NAMCOT-163", (19,0,8,0));
-        map.insert("NAMCOT-3301", (0,0,0,0));
-        map.insert("NAMCOT-3302", (0,0,0,0));
-        map.insert("NAMCOT-3303", (0,0,0,0));
-        map.insert("NAMCOT-3304", (0,0,0,0));
-        map.insert("NAMCOT-3305", (0,0,0,0));
-        map.insert("NAMCOT-3311", (0,0,0,0));
-        map.insert("NAMCOT-3312", (0,0,0,0));
-        map.insert("NAMCOT-CNROM+WRAM", (3,2,2,0));
-        map.insert("NES-NTBROM", (68,1,8,0));
-        map.insert("SUNSOFT_UNROM", (93,0,0,8));
+        map.insert("ACCLAIM-MC-ACC", (4,3,0,0,""));
+        map.insert("BANDAI-FCG-1", (16,0,0,0,""));
+        map.insert("BANDAI-FCG-2", (16,0,0,0,""));
+        map.insert("BANDAI-LZ93D50", (16,0,0,0,""));
+        map.insert("BANDAI-LZ93D50+24C01", (159,0,128,0,""));
+        map.insert("BANDAI-LZ93D50+24C02", (16,0,256,0,""));
+        map.insert("BANDAI-PT-554", (3,2,0,0,""));
+        map.insert("IREM-FCG-1", (16,0,0,0,""));
+        map.insert("JALECO-JF01", (0,0,0,0,""));
+        map.insert("JALECO-JF02", (0,0,0,0,""));
+        map.insert("JALECO-JF03", (0,0,0,0,""));
+        map.insert("JALECO-JF04", (0,0,0,0,""));
+        map.insert("JALECO-JF15", (2,2,0,8,""));
+        map.insert("JALECO-JF18", (2,2,0,8,""));
+        map.insert("JALECO-JF23", (18,0,0,0,""));
+        map.insert("JALECO-JF24", (18,0,0,0,""));
+        map.insert("JALECO-JF25", (18,0,0,0,""));
+        map.insert("JALECO-JF27", (18,0,8,0,""));
+        map.insert("JALECO-JF29", (18,0,0,0,""));
+        map.insert("JALECO-JF37", (18,0,0,0,""));
+        map.insert("JALECO-JF40", (18,0,8,0,""));
+        map.insert("NAMCOT-129", (19,0,0,0,""));
+        map.insert("NAMCOT-163", (19,0,8,0,""));
+        map.insert("NAMCOT-3301", (0,0,0,0,""));
+        map.insert("NAMCOT-3302", (0,0,0,0,""));
+        map.insert("NAMCOT-3303", (0,0,0,0,""));
+        map.insert("NAMCOT-3304", (0,0,0,0,""));
+        map.insert("NAMCOT-3305", (0,0,0,0,""));
+        map.insert("NAMCOT-3311", (0,0,0,0,""));
+        map.insert("NAMCOT-3312", (0,0,0,0,""));
+        map.insert("NAMCOT-CNROM+WRAM", (3,2,2,0,""));
+        map.insert("NES-NTBROM", (68,1,8,0,""));
+        map.insert("SUNSOFT_UNROM", (93,0,0,8,""));
         // boards made by unlicensed and bootleg publishers
-        map.insert("SL1632", (14,0,0,0));
-        map.insert("AC-08", (42,0,0,8));
-        map.insert("LH-09", (42,0,0,0));
-        map.insert("SUPERVISION16IN1", (53,0,0,8));
-        map.insert("SUPERHIK8IN1", (45,0,0,0));
-        map.insert("STREETFIGTER-GAME4IN1", (49,0,0,0));
-        map.insert("MARIO1-MALEE2", (42,0,2,0));
-        map.insert("D1038", (59,0,0,0));
-        map.insert("T3H53", (59,0,0,0));
-        map.insert("SA-016-1M", (79,0,0,0));
-        map.insert("VRC7", (85,0,0,0));
-        map.insert("SC-127", (90,0,8,0));
-        map.insert("BB", (108,0,0,0));
-        map.insert("SL12", (108,0,0,0));
-        map.insert("H2288", (123,0,0,0));
-        map.insert("22211", (132,0,0,0));
-        map.insert("SA-72008", (133,0,0,0));
-        map.insert("T4A54A", (134,0,0,0));
-        map.insert("SACHEN-8259D", (137,0,0,0));
-        map.insert("SACHEN-8259B", (138,0,0,0));
-        map.insert("SACHEN-8259C", (139,0,0,0));
-        map.insert("SACHEN-8259A", (141,0,0,0));
-        map.insert("KS7032", (142,0,0,0));
-        map.insert("SA-NROM", (143,0,0,0));
-        map.insert("SA-72007", (145,0,0,0));
-        map.insert("TC-U01-1.5M", (147,0,0,0));
-        map.insert("SA-0037", (148,0,0,0));
-        map.insert("SA-0036", (149,0,0,0));
-        map.insert("SACHEN-74LS374N", (150,0,0,0));
-        map.insert("FS304", (162,0,8,8));
-        map.insert("SUPER24IN1SC03", (176,0,0,8));
-        map.insert("FK23C", (176,0,0,256));
-        map.insert("FK23CA", (176,0,0,256));
-        map.insert("WAIXING-FS005", (176,0,32,8));
-        map.insert("NOVELDIAMOND9999999IN1", (201,0,0,0));
-        map.insert("JC-016-2", (205,0,0,0));
-        map.insert("8237", (215,0,0,0));
-        map.insert("8237A", (215,1,0,0));
-        map.insert("N625092", (221,0,0,0));
-        map.insert("GHOSTBUSTERS63IN1", (226,0,0,0));
-        map.insert("42IN1RESETSWITCH", (233,0,0,0));
-        map.insert("150IN1A", (235,0,0,0));
-        map.insert("212-HONG-KONG", (235,0,0,0));
-        map.insert("70IN1", (236,0,0,0));
-        map.insert("70IN1B", (236,0,0,0));
-        map.insert("603-5052", (238,0,0,0));
-        map.insert("WAIXING-FW01", (227,0,8,0));
-        map.insert("43272", (227,0,8,0));
-        map.insert("ONEBUS", (256,0,8,0));
-        map.insert("DANCE", (256,0,0,0));
-        map.insert("PEC-586", (257,0,8,0));
-        map.insert("158B", (258,0,0,0));
-        map.insert("F-15", (259,0,0,0));
-        map.insert("HPXX", (260,0,8,0));
-        map.insert("HP2018-A", (260,0,8,0));
-        map.insert("810544-C-A1", (261,0,0,0));
-        map.insert("SHERO", (262,0,0,8));
-        map.insert("KOF97", (263,0,0,0));
-        map.insert("YOKO", (264,0,0,0));
-        map.insert("T-262", (265,0,0,8));
-        map.insert("CITYFIGHT", (266,0,0,0));
-        map.insert("COOLBOY", (268,0,0,256));
-        map.insert("MINDKIDS", (268,0,8,256));
-        map.insert("22026", (271,0,0,0));
-        map.insert("80013-B", (274,0,0,8));
-        map.insert("GKCXIN1", (288,0,0,0));
-        map.insert("GS-2004", (283,0,0,8));
-        map.insert("GS-2004", (283,0,0,8));
-        map.insert("A65AS", (285,0,0,8));
-        map.insert("BS-5", (286,0,0,0));
-        map.insert("411120-C", (287,0,0,0));
-        map.insert("K-3088", (287,0,0,0));
-        map.insert("60311C", (289,0,0,8));
-        map.insert("NTD-03", (290,0,0,0));
-        map.insert("DRAGONFIGHTER", (292,0,0,0));
-        map.insert("13IN1JY110", (295,0,0,8));
-        map.insert("TF1201", (298,0,0,0));
-        map.insert("11160", (299,0,0,0));
-        map.insert("190in1", (300,0,0,0));
-        map.insert("8157", (301,0,0,8));
-        map.insert("KS7057", (302,0,0,8));
-        map.insert("KS7017", (303,0,8,8));
-        map.insert("SMB2J", (304,0,0,0));
-        map.insert("KS7031", (305,0,0,8));
-        map.insert("KS7016", (306,0,0,8));
-        map.insert("KS7037", (307,0,8,8));
-        map.insert("TH2131-1", (308,0,0,0));
-        map.insert("LH51", (309,0,8,8));
-        map.insert("LH32", (125,0,8,8));
-        map.insert("KS7013B", (312,0,0,8));
-        map.insert("RESET-TXROM", (313,0,0,0));
-        map.insert("64IN1NOREPEAT", (314,0,0,0));
-        map.insert("830134C", (315,0,0,0));
-        map.insert("HP898F", (319,0,0,0));
-        map.insert("830425C-4391T", (320,0,0,8));
-        map.insert("K-3033", (322,0,0,0));
-        map.insert("MALISB", (325,0,0,0));
-        map.insert("10-24-C-A1", (327,0,8,8));
-        map.insert("RT-01", (328,0,0,0));
-        map.insert("EDU2000", (329,0,32,8));
-        map.insert("12-IN-1", (331,0,0,0));
-        map.insert("WS", (332,0,0,0));
-        map.insert("NEWSTAR-GRM070-8IN1", (333,0,0,0));
-        map.insert("8-IN-1", (333,0,0,0));
-        map.insert("CTC-09", (335,0,0,0));
-        map.insert("K-3046", (336,0,0,8));
-        map.insert("CTC-12IN1", (337,0,0,8));
-        map.insert("SA005-A", (338,0,0,0));
-        map.insert("K-3006", (339,0,0,0));
-        map.insert("K-3036", (340,0,0,8));
-        map.insert("TJ-03", (341,0,0,0));
-        map.insert("GN-26", (344,0,0,0));
-        map.insert("L6IN1", (345,0,0,0));
-        map.insert("KS7012\"", (346,0,8,8));
-        map.insert("KS7030", (347,0,8,8));
-        map.insert("830118C", (348,0,0,0));
-        map.insert("G-146", (349,0,0,8));
-        map.insert("891227", (350,0,0,8));
-        map.insert("3D-BLOCK", (355,0,0,8));
-        map.insert("SA-9602B", (513,0,0,32));
-        map.insert("DANCE2000", (518,0,8,8));
-        map.insert("EH8813A", (519,0,0,0));
-        map.insert("DREAMTECH01", (521,0,0,8));
-        map.insert("LH10", (522,0,8,8));
-        map.insert("900218", (524,0,0,0));
-        map.insert("KS7021A", (525,0,0,0));
-        map.insert("BJ-56", (526,0,8,0));
-        map.insert("AX-40G", (527,0,0,0));
-        map.insert("831128C", (528,0,8,0));
-        map.insert("T-230", (529,0,0,0));
-        map.insert("AX5705", (530,0,0,0));
+        map.insert("SL1632", (14,0,0,0,"UNL-"));
+        map.insert("AC-08", (42,0,0,8,"UNL-"));
+        map.insert("LH-09", (42,0,0,0,"UNL-"));
+        map.insert("SUPERVISION16IN1", (53,0,0,8,"BMC-"));
+        map.insert("SUPERHIK8IN1", (45,0,0,0,"BMC-"));
+        map.insert("STREETFIGTER-GAME4IN1", (49,0,0,0,"BMC-"));
+        map.insert("MARIO1-MALEE2", (42,0,2,0,"UNL-"));
+        map.insert("D1038", (59,0,0,0,"BMC-"));
+        map.insert("T3H53", (59,0,0,0,"BMC-"));
+        map.insert("SA-016-1M", (79,0,0,0,"UNL-"));
+        map.insert("VRC7", (85,0,0,0,"UNL-"));
+        map.insert("SC-127", (90,0,8,0,"UNL-"));
+        map.insert("BB", (108,0,0,0,"UNL-"));
+        map.insert("SL12", (108,0,0,0,"UNL-"));
+        map.insert("H2288", (123,0,0,0,"UNL-"));
+        map.insert("22211", (132,0,0,0,"UNL-"));
+        map.insert("SA-72008", (133,0,0,0,"UNL-"));
+        map.insert("T4A54A", (134,0,0,0,"UNL-"));
+        map.insert("SACHEN-8259D", (137,0,0,0,"UNL-"));
+        map.insert("SACHEN-8259B", (138,0,0,0,"UNL-"));
+        map.insert("SACHEN-8259C", (139,0,0,0,"UNL-"));
+        map.insert("SACHEN-8259A", (141,0,0,0,"UNL-"));
+        map.insert("KS7032", (142,0,0,0,"UNL-"));
+        map.insert("SA-NROM", (143,0,0,0,"UNL-"));
+        map.insert("SA-72007", (145,0,0,0,"UNL-"));
+        map.insert("TC-U01-1.5M", (147,0,0,0,"UNL-"));
+        map.insert("SA-0037", (148,0,0,0,"UNL-"));
+        map.insert("SA-0036", (149,0,0,0,"UNL-"));
+        map.insert("SACHEN-74LS374N", (150,0,0,0,"UNL-"));
+        map.insert("FS304", (162,0,8,8,"UNL-"));
+        map.insert("SUPER24IN1SC03", (176,0,0,8,"BMC-"));
+        map.insert("FK23C", (176,0,0,256,"BMC-"));
+        map.insert("FK23CA", (176,0,0,256,"BMC-"));
+        map.insert("WAIXING-FS005", (176,0,32,8,"UNL-"));
+        map.insert("NOVELDIAMOND9999999IN1", (201,0,0,0,"BMC-"));
+        map.insert("JC-016-2", (205,0,0,0,"BMC-"));
+        map.insert("8237", (215,0,0,0,"UNL-"));
+        map.insert("8237A", (215,1,0,0,"UNL-"));
+        map.insert("N625092", (221,0,0,0,"UNL-"));
+        map.insert("GHOSTBUSTERS63IN1", (226,0,0,0,"BMC-"));
+        map.insert("42IN1RESETSWITCH", (233,0,0,0,"BMC-"));
+        map.insert("150IN1A", (235,0,0,0,"BMC-"));
+        map.insert("212-HONG-KONG", (235,0,0,0,"BMC-"));
+        map.insert("70IN1", (236,0,0,0,"BMC-"));
+        map.insert("70IN1B", (236,0,0,0,"BMC-"));
+        map.insert("603-5052", (238,0,0,0,"BMC-"));
+        map.insert("WAIXING-FW01", (227,0,8,0,"UNL-"));
+        map.insert("43272", (227,0,8,0,"UNL-"));
+        map.insert("ONEBUS", (256,0,8,0,"UNL-"));
+        map.insert("DANCE", (256,0,0,0,"UNL-"));
+        map.insert("PEC-586", (257,0,8,0,"UNL-"));
+        map.insert("158B", (258,0,0,0,"UNL-"));
+        map.insert("F-15", (259,0,0,0,"UNL-"));
+        map.insert("HPXX", (260,0,8,0,"BMC-"));
+        map.insert("HP2018-A", (260,0,8,0,"BMC-"));
+        map.insert("810544-C-A1", (261,0,0,0,"BMC-"));
+        map.insert("SHERO", (262,0,0,8,"UNL-"));
+        map.insert("KOF97", (263,0,0,0,"UNL-"));
+        map.insert("YOKO", (264,0,0,0,"UNL-"));
+        map.insert("T-262", (265,0,0,8,"BMC-"));
+        map.insert("CITYFIGHT", (266,0,0,0,"UNL-"));
+        map.insert("COOLBOY", (268,0,0,256,"UNL-"));
+        map.insert("MINDKIDS", (268,0,8,256,"UNL-"));
+        map.insert("22026", (271,0,0,0,"UNL-"));
+        map.insert("80013-B", (274,0,0,8,"UNL-"));
+        map.insert("GKCXIN1", (288,0,0,0,"BMC-"));
+        map.insert("GS-2004", (283,0,0,8,"UNL-"));
+        map.insert("GS-2004", (283,0,0,8,"UNL-"));
+        map.insert("A65AS", (285,0,0,8,"BMC-"));
+        map.insert("BS-5", (286,0,0,0,"BMC-"));
+        map.insert("411120-C", (287,0,0,0,"BMC-"));
+        map.insert("K-3088", (287,0,0,0,"BMC-"));
+        map.insert("60311C", (289,0,0,8,"BMC-"));
+        map.insert("NTD-03", (290,0,0,0,"BMC-"));
+        map.insert("DRAGONFIGHTER", (292,0,0,0,"UNL-"));
+        map.insert("13IN1JY110", (295,0,0,8,"BMC-"));
+        map.insert("TF1201", (298,0,0,0,"UNL-"));
+        map.insert("11160", (299,0,0,0,"BMC-"));
+        map.insert("190in1", (300,0,0,0,"BMC-"));
+        map.insert("8157", (301,0,0,8,"BMC-"));
+        map.insert("KS7057", (302,0,0,8,"UNL-"));
+        map.insert("KS7017", (303,0,8,8,"UNL-"));
+        map.insert("SMB2J", (304,0,0,0,"UNL-"));
+        map.insert("KS7031", (305,0,0,8,"UNL-"));
+        map.insert("KS7016", (306,0,0,8,"UNL-"));
+        map.insert("KS7037", (307,0,8,8,"UNL-"));
+        map.insert("TH2131-1", (308,0,0,0,"BMC-"));
+        map.insert("LH51", (309,0,8,8,"UNL-"));
+        map.insert("LH32", (125,0,8,8,"UNL-"));
+        map.insert("KS7013B", (312,0,0,8,"UNL-"));
+        map.insert("RESET-TXROM", (313,0,0,0,"UNL-"));
+        map.insert("64IN1NOREPEAT", (314,0,0,0,"BMC-"));
+        map.insert("830134C", (315,0,0,0,"BMC-"));
+        map.insert("HP898F", (319,0,0,0,"BMC-"));
+        map.insert("830425C-4391T", (320,0,0,8,"BMC-"));
+        map.insert("K-3033", (322,0,0,0,"BMC-"));
+        map.insert("MALISB", (325,0,0,0,"UNL-"));
+        map.insert("10-24-C-A1", (327,0,8,8,"BMC-"));
+        map.insert("RT-01", (328,0,0,0,"UNL-"));
+        map.insert("EDU2000", (329,0,32,8,"UNL-"));
+        map.insert("12-IN-1", (331,0,0,0,"BMC-"));
+        map.insert("WS", (332,0,0,0,"BMC-"));
+        map.insert("NEWSTAR-GRM070-8IN1", (333,0,0,0,"BMC-"));
+        map.insert("8-IN-1", (333,0,0,0,"BMC-"));
+        map.insert("CTC-09", (335,0,0,0,"BMC-"));
+        map.insert("K-3046", (336,0,0,8,"BMC-"));
+        map.insert("CTC-12IN1", (337,0,0,8,"BMC-"));
+        map.insert("SA005-A", (338,0,0,0,"BMC-"));
+        map.insert("K-3006", (339,0,0,0,"BMC-"));
+        map.insert("K-3036", (340,0,0,8,"BMC-"));
+        map.insert("TJ-03", (341,0,0,0,"BMC-"));
+        map.insert("GN-26", (344,0,0,0,"BMC-"));
+        map.insert("L6IN1", (345,0,0,0,"BMC-"));
+        map.insert("KS7012\"", (346,0,8,8,"UNL-"));
+        map.insert("KS7030", (347,0,8,8,"UNL-"));
+        map.insert("830118C", (348,0,0,0,"BMC-"));
+        map.insert("G-146", (349,0,0,8,"BMC-"));
+        map.insert("891227", (350,0,0,8,"BMC-"));
+        map.insert("3D-BLOCK", (355,0,0,8,"UNL-"));
+        map.insert("SA-9602B", (513,0,0,32,"UNL-"));
+        map.insert("DANCE2000", (518,0,8,8,"UNL-"));
+        map.insert("EH8813A", (519,0,0,0,"UNL-"));
+        map.insert("DREAMTECH01", (521,0,0,8,"UNL-"));
+        map.insert("LH10", (522,0,8,8,"UNL-"));
+        map.insert("900218", (524,0,0,0,"BMC-"));
+        map.insert("KS7021A", (525,0,0,0,"UNL-"));
+        map.insert("BJ-56", (526,0,8,0,"UNL-"));
+        map.insert("AX-40G", (527,0,0,0,"UNL-"));
+        map.insert("831128C", (528,0,8,0,"BMC-"));
+        map.insert("T-230", (529,0,0,0,"UNL-"));
+        map.insert("AX5705", (530,0,0,0,"UNL-"));
         // homebrew boards
-        map.insert("COOLGIRL", (342,0,32,256));
-        map.insert("DRIPGAME", (284,0,8,0));
-        map.insert("FARID_SLROM_8-IN-1", (323,0,0,0));
-        map.insert("FARID_UNROM_8-IN-1", (324,0,0,8));
-        map.insert("RET-CUFROM", (29,0,0,32));
+        map.insert("COOLGIRL", (342,0,32,256,""));
+        map.insert("DRIPGAME", (284,0,8,0,"UNL-"));
+        map.insert("FARID_SLROM_8-IN-1", (323,0,0,0,""));
+        map.insert("FARID_UNROM_8-IN-1", (324,0,0,8,""));
+        map.insert("RET-CUFROM", (29,0,0,32,""));
 
         map
     };
 }
 
+// boards written for a mapper and submapper when converting to unif, the first board
+// matching the ram sizes in kb is used and the first one listed otherwise.
+// mapper 0 picks NROM-128 or NROM-256 by prg size instead. submapper 0 entries cover
+// ines 1.0 roms of boards mapped to a submapper
+const UNIF_PREFERRED_BOARDS: [(u32, u8, &str); 34] = [
+    (1, 0, "SLROM"), (1, 0, "SKROM"), (1, 0, "SGROM"), (1, 0, "SNROM"), (1, 0, "SOROM"), (1, 0, "SXROM"),
+    (1, 5, "SEROM"),
+    (2, 0, "UNROM"),
+    (2, 2, "UNROM"),
+    (3, 0, "CNROM"),
+    (3, 2, "CNROM"),
+    (4, 0, "TLROM"), (4, 0, "TKROM"), (4, 0, "TGROM"), (4, 0, "TNROM"),
+    (4, 1, "HKROM"),
+    (4, 3, "ACCLAIM-MC-ACC"),
+    (5, 0, "ELROM"), (5, 0, "EKROM"), (5, 0, "ETROM"), (5, 0, "EWROM"),
+    (7, 0, "AOROM"),
+    (7, 1, "ANROM"),
+    (7, 2, "AMROM"),
+    (9, 0, "PNROM"),
+    (10, 0, "FKROM"),
+    (13, 0, "CPROM"),
+    (34, 0, "BNROM"),
+    (34, 2, "BNROM"),
+    (68, 1, "NES-NTBROM"),
+    (118, 0, "TLSROM"), (118, 0, "TKSROM"),
+    (119, 0, "TQROM"),
+    (185, 0, "CNROM+SECURITY"),
+];
+
+// latest revision of the unif specification
+const UNIF_REVISION: u32 = 7;
+
 #[derive(Debug, Copy, Clone, PartialEq, FromPrimitive, ToPrimitive)]
 pub enum NametableMirroring {
     Horizontal = 0,
@@ -290,6 +322,7 @@ pub enum GameInputType {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Unif {
+    pub revision: u32,
     pub name: Option<String>,
     pub read: Option<String>,
     pub tvci: Option<TvSystem>,
@@ -302,11 +335,16 @@ pub struct Unif {
     pub chr_crc: u32,
     pub prg_data: Vec<u8>,
     pub chr_data: Vec<u8>,
+    // sizes of the PRGn/CHRn chunks the data is split into, zero for a missing chunk.
+    // data is written as a single chunk when the sizes don't add up to its length
+    pub prg_chunk_sizes: [u32; 16],
+    pub chr_chunk_sizes: [u32; 16],
 }
 
 impl Unif {
     pub fn new() -> Unif {
         Unif {
+            revision: UNIF_REVISION,
             name: None,
             read: None,
             tvci: None,
@@ -319,21 +357,25 @@ impl Unif {
             mapr: String::new(),
             prg_data: Vec::new(),
             chr_data: Vec::new(),
+            prg_chunk_sizes: [0; 16],
+            chr_chunk_sizes: [0; 16],
         }
     }
     /// Load and parse .unif/.unf file
     /// 
     /// # Examples
     /// 
-    ///  ```
+    ///  ```no_run
+    /// # let arg = std::fs::File::open("rom.unf").unwrap();
     /// let unif = nes_rom::unif::Unif::from_rom(arg);
     ///  ```
     pub fn from_rom<R: Read + Seek>(mut file: R) -> Result<Unif, RomError> {
         let mut buf: [u8; 32] = [0; 32];
-        file.read_exact(&mut buf)?;
+        file.read_exact(&mut buf).map_err(|e| RomError::from(e).with_format(RomFormat::Unif).with_field("header").with_offset(0))?;
         // check header
-        if &buf[0..4] != super::UNIF_GUARD {
-            return Err(RomError::InvalidFormat);
+        if buf[0..4] != super::UNIF_GUARD {
+            return Err(unif_error(ErrorKind::InvalidFormat, "magic").with_offset(0)
+                .with_values(format!("{:02X?}", super::UNIF_GUARD), format!("{:02X?}", &buf[0..4])));
         }  
 
         let file_size = file.seek(io::SeekFrom::End(0))?;
@@ -343,25 +385,31 @@ impl Unif {
         let mut prg_chunks: [Vec<u8>; 16] = Default::default();
         let mut chr_chunks: [Vec<u8>; 16] = Default::default();
         let mut unif = Unif::new();
+        unif.revision = get_chunk_len(&buf[4..8]);
         
         while read_chunk(&mut file, &mut unif, &mut prg_chunks, &mut chr_chunks, file_size)? {
 
         }
 
         // copy finalized rom data
-        for chunk in &mut prg_chunks {
-            unif.prg_data.append(&mut *chunk);
+        for (i, chunk) in prg_chunks.iter_mut().enumerate() {
+            unif.prg_chunk_sizes[i] = chunk.len() as u32;
+            unif.prg_data.append(chunk);
         }
 
-        for chunk in &mut chr_chunks {
-            unif.chr_data.append(&mut *chunk);
+        for (i, chunk) in chr_chunks.iter_mut().enumerate() {
+            unif.chr_chunk_sizes[i] = chunk.len() as u32;
+            unif.chr_data.append(chunk);
         }
 
         unif.prg_crc = crc32::crc32_ieee_from_slice(&unif.prg_data);     
         unif.chr_crc = crc32::crc32_ieee_from_slice(&unif.chr_data); 
 
-        if unif.mapr.is_empty() || unif.prg_data.is_empty() || unif.chr_data.is_empty() {
-            return Err(RomError::InvalidRom);
+        if unif.mapr.is_empty() {
+            return Err(unif_error(ErrorKind::InvalidRom, "MAPR chunk").with_values("board name", "missing chunk"));
+        }
+        if unif.prg_data.is_empty() {
+            return Err(unif_error(ErrorKind::InvalidRom, "PRG chunks").with_values("prg rom data", "missing chunks"));
         }
         Ok(unif)
     }
@@ -370,7 +418,8 @@ impl Unif {
     /// 
     /// # Examples
     /// 
-    ///  ```
+    ///  ```no_run
+    /// # let unif = nes_rom::unif::Unif::new();
     /// let ines = unif.into_ines();
     ///  ```
     pub fn into_ines(self) ->Result<ines::Ines, RomError> {
@@ -380,11 +429,10 @@ impl Unif {
         let (mapper, submapper, prg_ram, chr_ram) = get_mapper_id(self.mapr.as_str())?;
         i.mapper = mapper;
         i.submapper = submapper;
-        i.ram = ines::Ram::Nes2{prg_ram: prg_ram, prg_nvram: 0, chr_ram: chr_ram, chr_nvram: 0};
+        i.ram = ines::Ram::Nes2{prg_ram: prg_ram * 1024, prg_nvram: 0, chr_ram: chr_ram * 1024, chr_nvram: 0};
 
-        match self.batr {
-            Some(contains_nvram) => i.contains_nvram = contains_nvram,
-            None => (),
+        if let Some(contains_nvram) = self.batr {
+            i.contains_nvram = contains_nvram;
         }
 
         let tv: TvSystem = self.tvci.unwrap_or(TvSystem::NTSC);
@@ -427,14 +475,15 @@ impl Unif {
         i.prg_data = self.prg_data;
         i.chr_data = self.chr_data;
 
-        return Ok(i);
+        Ok(i)
     }
 
     /// Convert unif to ines format, clone rom data
     /// 
     /// # Examples
     /// 
-    ///  ```
+    ///  ```no_run
+    /// # let unif = nes_rom::unif::Unif::new();
     /// let ines = unif.to_ines();
     ///  ```
     pub fn to_ines(&self) ->Result<ines::Ines, RomError> {
@@ -444,11 +493,10 @@ impl Unif {
         let (mapper, submapper, prg_ram, chr_ram) = get_mapper_id(self.mapr.as_str())?;
         i.mapper = mapper;
         i.submapper = submapper;
-        i.ram = ines::Ram::Nes2{prg_ram: prg_ram, prg_nvram: 0, chr_ram: chr_ram, chr_nvram: 0};
+        i.ram = ines::Ram::Nes2{prg_ram: prg_ram * 1024, prg_nvram: 0, chr_ram: chr_ram * 1024, chr_nvram: 0};
 
-        match self.batr {
-            Some(contains_nvram) => i.contains_nvram = contains_nvram,
-            None => (),
+        if let Some(contains_nvram) = self.batr {
+            i.contains_nvram = contains_nvram;
         }
 
         let tv: TvSystem = self.tvci.unwrap_or(TvSystem::NTSC);
@@ -491,83 +539,204 @@ impl Unif {
         i.prg_data = self.prg_data.clone();
         i.chr_data = self.chr_data.clone();
 
-        return Ok(i);
+        Ok(i)
+    }
+
+    /// Serialize to .unif file bytes
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let unif = nes_rom::unif::Unif::new();
+    /// let bytes = unif.to_bytes().unwrap();
+    ///  ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, RomError> {
+        let mut bytes = Vec::with_capacity(32 + self.prg_data.len() + self.chr_data.len() + 256);
+        self.write_to(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Write .unif file
+    /// 
+    /// # Examples
+    /// 
+    ///  ```no_run
+    /// # let unif = nes_rom::unif::Unif::new();
+    /// let file = std::fs::File::create("rom.unf").unwrap();
+    /// unif.write_to(file).unwrap();
+    ///  ```
+    pub fn write_to<W: Write>(&self, mut file: W) -> Result<(), RomError> {
+        if self.mapr.is_empty() {
+            return Err(unif_error(ErrorKind::InvalidRom, "MAPR chunk").with_values("board name", "empty"));
+        }
+
+        let mut header: [u8; 32] = [0; 32];
+        header[0..4].copy_from_slice(&super::UNIF_GUARD);
+        header[4..8].copy_from_slice(&self.revision.to_le_bytes());
+        file.write_all(&header)?;
+
+        write_chunk(&mut file, b"MAPR", &null_terminated(&self.mapr))?;
+        if let Some(ref name) = self.name {
+            write_chunk(&mut file, b"NAME", &null_terminated(name))?;
+        }
+        if let Some(ref read) = self.read {
+            write_chunk(&mut file, b"READ", &null_terminated(read))?;
+        }
+        if let Some(tvci) = self.tvci {
+            write_chunk(&mut file, b"TVCI", &[tvci as u8])?;
+        }
+        if let Some(ctrl) = self.ctrl {
+            write_chunk(&mut file, b"CTRL", &[ctrl as u8])?;
+        }
+        if let Some(mirr) = self.mirr {
+            write_chunk(&mut file, b"MIRR", &[mirr as u8])?;
+        }
+        if let Some(batr) = self.batr {
+            write_chunk(&mut file, b"BATR", &[batr as u8])?;
+        }
+        if let Some(vror) = self.vror {
+            write_chunk(&mut file, b"VROR", &[vror])?;
+        }
+
+        for (index, chunk) in split_chunks(&self.prg_data, &self.prg_chunk_sizes) {
+            write_chunk(&mut file, &indexed_chunk_type(b"PCK", index), &crc32::crc32_ieee_from_slice(chunk).to_le_bytes())?;
+            write_chunk(&mut file, &indexed_chunk_type(b"PRG", index), chunk)?;
+        }
+        for (index, chunk) in split_chunks(&self.chr_data, &self.chr_chunk_sizes) {
+            write_chunk(&mut file, &indexed_chunk_type(b"CCK", index), &crc32::crc32_ieee_from_slice(chunk).to_le_bytes())?;
+            write_chunk(&mut file, &indexed_chunk_type(b"CHR", index), chunk)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Unif {
+    fn default() -> Self {
+        Unif::new()
+    }
+}
+
+impl RomInfo for Unif {
+    fn format(&self) -> RomFormat {
+        RomFormat::Unif
+    }
+
+    fn prg(&self) -> &[u8] {
+        &self.prg_data
+    }
+
+    fn chr(&self) -> &[u8] {
+        &self.chr_data
+    }
+
+    fn prg_crc(&self) -> u32 {
+        self.prg_crc
+    }
+
+    fn chr_crc(&self) -> u32 {
+        self.chr_crc
+    }
+
+    fn mapper(&self) -> Option<u32> {
+        get_mapper_id(&self.mapr).ok().map(|(mapper, _, _, _)| mapper)
+    }
+
+    fn submapper(&self) -> Option<u8> {
+        get_mapper_id(&self.mapr).ok().map(|(_, submapper, _, _)| submapper)
+    }
+
+    fn board(&self) -> Option<&str> {
+        Some(&self.mapr)
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        match self.mirr.unwrap_or(NametableMirroring::MapperControlled) {
+            NametableMirroring::Horizontal => Mirroring::Horizontal,
+            NametableMirroring::Vertical => Mirroring::Vertical,
+            NametableMirroring::ScreenAOnly => Mirroring::ScreenAOnly,
+            NametableMirroring::ScreenBOnly => Mirroring::ScreenBOnly,
+            NametableMirroring::FourScreens => Mirroring::FourScreens,
+            NametableMirroring::MapperControlled => Mirroring::MapperControlled,
+        }
+    }
+
+    fn region(&self) -> Region {
+        match self.tvci.unwrap_or(TvSystem::NTSC) {
+            TvSystem::NTSC => Region::NTSC,
+            TvSystem::PAL => Region::PAL,
+            TvSystem::MultRegion => Region::MultRegion,
+        }
+    }
+
+    fn has_battery(&self) -> bool {
+        self.batr.unwrap_or(false)
     }
 }
 
 fn read_chunk<R: Read + Seek>(file: &mut R, unif: &mut Unif, prg_chunks: &mut [Vec<u8>], chr_chunks: &mut [Vec<u8>], end_of_file: u64) -> Result<bool, RomError> {
+    let offset = file.stream_position()?;
     let mut type_buf =  [0u8; 4];
     let mut len_buf = [0u8; 4];
-    file.read_exact(&mut type_buf)?;
-    file.read_exact(&mut len_buf)?;
+    file.read_exact(&mut type_buf).map_err(|e| RomError::from(e).with_format(RomFormat::Unif).with_field("chunk header").with_offset(offset))?;
+    file.read_exact(&mut len_buf).map_err(|e| RomError::from(e).with_format(RomFormat::Unif).with_field("chunk header").with_offset(offset))?;
 
-    let chunk_type = unsafe {
-        str::from_utf8_unchecked(&type_buf)
-    };
+    let chunk_type = String::from_utf8_lossy(&type_buf);
+    let chunk_field = format!("{} chunk", chunk_type);
 
     let len = get_chunk_len(&len_buf);
-    let mut chunk_buf = vec![0u8; len as usize];    
+    if offset + 8 + len as u64 > end_of_file {
+        return Err(unif_error(ErrorKind::InvalidRom, chunk_field).with_offset(offset)
+            .with_values(format!("{} bytes", len), format!("{} bytes", end_of_file - offset - 8)));
+    }
+    // single byte chunks
+    if is_byte_chunk(&type_buf) && len == 0 {
+        return Err(unif_error(ErrorKind::InvalidRom, chunk_field).with_offset(offset).with_values("1 byte", "0 bytes"));
+    }
+    let mut chunk_buf = vec![0u8; len as usize];
+    file.read_exact(&mut chunk_buf).map_err(|e| RomError::from(e).with_format(RomFormat::Unif).with_field(chunk_field.as_str()).with_offset(offset))?;
 
     if chunk_type == "MAPR" {
-        file.read_exact(&mut chunk_buf)?;
-        unif.mapr = unsafe {
-            // unif uses null terminated utf-8 strings
-            String::from_utf8_unchecked(chunk_buf).trim_end_matches(char::from(0)).to_string()
-        };
+        // unif uses null terminated utf-8 strings
+        unif.mapr = chunk_string(&chunk_buf);
     }
     else if chunk_type == "NAME" {
-        file.read_exact(&mut chunk_buf)?;
-        unif.name = Some(unsafe {
-            String::from_utf8_unchecked(chunk_buf).trim_end_matches(char::from(0)).to_string()
-        });
+        unif.name = Some(chunk_string(&chunk_buf));
     }
     else if chunk_type == "READ" {
-        file.read_exact(&mut chunk_buf)?;
-        unif.read = Some(unsafe {
-            String::from_utf8_unchecked(chunk_buf).trim_end_matches(char::from(0)).to_string()
-        });
+        unif.read = Some(chunk_string(&chunk_buf));
     }
     else if chunk_type.contains("PRG") {
-        file.read_exact(&mut chunk_buf)?;
         // the type identifier fixed 4 bytes with last byte representing hex value
-        let index = get_index_from_hex_ascii(type_buf[3])?;
+        let index = get_index_from_hex_ascii(type_buf[3]).map_err(|e| e.with_field(chunk_field.as_str()).with_offset(offset))?;
         prg_chunks[index] = chunk_buf.to_vec();
 
     }
     else if chunk_type.contains("CHR") {
-        file.read_exact(&mut chunk_buf)?;
         // the type identifier fixed 4 bytes with last byte representing hex value
-        let index = get_index_from_hex_ascii(type_buf[3])?;
+        let index = get_index_from_hex_ascii(type_buf[3]).map_err(|e| e.with_field(chunk_field.as_str()).with_offset(offset))?;
         chr_chunks[index] = chunk_buf.to_vec();
     }
     else if chunk_type == "TVCI" {
-        file.read_exact(&mut chunk_buf)?;
         unif.tvci = TvSystem::from_u8(chunk_buf[0]);
     }
     else if chunk_type == "CTRL" {
-        file.read_exact(&mut chunk_buf)?;
         unif.ctrl = GameInputType::from_u8(chunk_buf[0]);
     }
     else if chunk_type == "MIRR" {
-        file.read_exact(&mut chunk_buf)?;
         unif.mirr = NametableMirroring::from_u8(chunk_buf[0]);
     }
     else if chunk_type == "BATR" {
-        file.read_exact(&mut chunk_buf)?;
         unif.batr = match chunk_buf[0] {
             0 => Some(false),
             _ => Some(true),
         };
     }
     else if chunk_type == "VROR" {
-        file.read_exact(&mut chunk_buf)?;
         unif.vror = Some(chunk_buf[0]);
     }
-    else {
-        file.read_exact(&mut chunk_buf)?;
-    }
 
-    let cur_pos = file.seek(io::SeekFrom::Current(0))?;
+    let cur_pos = file.stream_position()?;
 
     if cur_pos < end_of_file {
         Ok(true)
@@ -577,8 +746,54 @@ fn read_chunk<R: Read + Seek>(file: &mut R, unif: &mut Unif, prg_chunks: &mut [V
     }
 }
 
+// chunks holding a single byte value
+fn is_byte_chunk(chunk_type: &[u8; 4]) -> bool {
+    matches!(chunk_type, b"TVCI" | b"CTRL" | b"MIRR" | b"BATR" | b"VROR")
+}
+
+fn write_chunk<W: Write>(file: &mut W, chunk_type: &[u8; 4], data: &[u8]) -> Result<(), RomError> {
+    file.write_all(chunk_type)?;
+    file.write_all(&(data.len() as u32).to_le_bytes())?;
+    file.write_all(data)?;
+    Ok(())
+}
+
+// splits rom data along the chunk sizes, empty data has no chunks
+fn split_chunks<'a>(data: &'a [u8], sizes: &[u32; 16]) -> Vec<(usize, &'a [u8])> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+    if sizes.iter().map(|s| *s as usize).sum::<usize>() != data.len() {
+        return vec![(0, data)];
+    }
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    for (index, size) in sizes.iter().enumerate().filter(|(_, size)| **size > 0) {
+        chunks.push((index, &data[start..start + *size as usize]));
+        start += *size as usize;
+    }
+    chunks
+}
+
+// chunk type with the chunk index as last hex digit e.g. PRG0
+fn indexed_chunk_type(prefix: &[u8; 3], index: usize) -> [u8; 4] {
+    [prefix[0], prefix[1], prefix[2], b"0123456789ABCDEF"[index]]
+}
+
+// invalid utf-8 is replaced rather than trusted
+fn chunk_string(chunk_buf: &[u8]) -> String {
+    String::from_utf8_lossy(chunk_buf).trim_end_matches(char::from(0)).to_string()
+}
+
+fn null_terminated(s: &str) -> Vec<u8> {
+    let mut bytes = s.as_bytes().to_vec();
+    bytes.push(0);
+    bytes
+}
+
 fn get_chunk_len(len_buf: &[u8]) -> u32 {
-    ((len_buf[0] as u32) | ((len_buf[1] as u32) << 8) | ((len_buf[2] as u32) << 16) | ((len_buf[3] as u32) << 24))
+    (len_buf[0] as u32) | ((len_buf[1] as u32) << 8) | ((len_buf[2] as u32) << 16) | ((len_buf[3] as u32) << 24)
 }
 
 fn get_index_from_hex_ascii(byte: u8) -> Result<usize, RomError> {
@@ -599,7 +814,7 @@ fn get_index_from_hex_ascii(byte: u8) -> Result<usize, RomError> {
         100 | 68 => Ok(13),
         101 | 69 => Ok(14),
         102 | 70 => Ok(15),
-        _ => Err(RomError::InvalidRom),
+        _ => Err(unif_error(ErrorKind::InvalidRom, "chunk index").with_values("hex digit", format!("{:#04X}", byte))),
     }
 }
 
@@ -608,7 +823,151 @@ fn get_mapper_id(mapr: &str) -> Result<(u32, u8, u32, u32), RomError> {
         trim_start_matches("BTL-").trim_start_matches("HVC-").trim_start_matches("BMC-").trim_start_matches("IREM-");
     
     match UNIF_BOARD_MAPPINGS.get(mapr_str) {
-            Some(info) => Ok(*info),
-            None => return Err(RomError::InvalidConversion),
+            Some(info) => Ok((info.0, info.1, info.2, info.3)),
+            None => Err(unif_error(ErrorKind::InvalidConversion, "MAPR board").with_values("board with ines mapping", mapr)),
+        }
+}
+
+// reverse lookup of the board mappings, prefers the boards listed in UNIF_PREFERRED_BOARDS
+// and otherwise a board matching the ram sizes in kb, or the alphabetically first board
+pub(crate) fn get_board_name(mapper: u32, submapper: u8, prg_size: usize, prg_ram: u32, chr_ram: u32) -> Result<String, RomError> {
+    let board_name = |board: &str| {
+        let prefix = UNIF_BOARD_MAPPINGS.get(board).map_or("", |info| info.4);
+        format!("{}{}", prefix, board)
+    };
+
+    if mapper == 0 && submapper == 0 {
+        return Ok(board_name(if prg_size <= 0x4000 { "NROM-128" } else { "NROM-256" }));
+    }
+
+    let preferred: Vec<&str> = UNIF_PREFERRED_BOARDS.iter()
+        .filter(|b| b.0 == mapper && b.1 == submapper)
+        .map(|b| b.2)
+        .collect();
+    let matches_ram = |board: &str| UNIF_BOARD_MAPPINGS.get(board).is_some_and(|info| info.2 == prg_ram && info.3 == chr_ram);
+    if let Some(board) = preferred.iter().find(|b| matches_ram(b)).or_else(|| preferred.first()) {
+        return Ok(board_name(board));
+    }
+
+    let mut boards: Vec<&'static str> = UNIF_BOARD_MAPPINGS.iter()
+        .filter(|(_, info)| info.0 == mapper && info.1 == submapper)
+        .map(|(name, _)| *name)
+        .collect();
+    boards.sort();
+
+    match boards.iter().find(|b| matches_ram(b)).or_else(|| boards.first()) {
+        Some(board) => Ok(board_name(board)),
+        None => Err(unif_error(ErrorKind::InvalidConversion, "MAPR board")
+            .with_values("unif board for mapper", format!("mapper {} submapper {}", mapper, submapper))),
+    }
+}
+
+fn unif_error<S: Into<String>>(kind: ErrorKind, field: S) -> RomError {
+    RomError::new(kind).with_format(RomFormat::Unif).with_field(field)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(chunk_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut bytes = chunk_type.to_vec();
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn unif_file(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = vec![0; 32];
+        bytes[0..4].copy_from_slice(&crate::UNIF_GUARD);
+        bytes[4] = 7;
+        for c in chunks {
+            bytes.extend_from_slice(c);
+        }
+        bytes
+    }
+
+    #[test]
+    fn invalid_utf8_strings_are_replaced() {
+        let rom = unif_file(&[
+            chunk(b"MAPR", b"NES-NROM-256\0"),
+            chunk(b"NAME", &[0x47, 0xFF, 0x4D, 0x00]),
+            chunk(b"PRG0", &[0; 0x8000]),
+        ]);
+
+        let unif = Unif::from_rom(io::Cursor::new(rom)).unwrap();
+        assert_eq!(unif.mapr, "NES-NROM-256");
+        assert_eq!(unif.name.as_deref(), Some("G\u{FFFD}M"));
+    }
+
+    #[test]
+    fn invalid_chunk_lengths() {
+        let e = Unif::from_rom(io::Cursor::new(unif_file(&[chunk(b"MAPR", b"NES-NROM-256\0"), chunk(b"MIRR", &[])]))).unwrap_err();
+        assert_eq!((e.kind(), e.field(), e.offset()), (ErrorKind::InvalidRom, Some("MIRR chunk"), Some(32 + 21)));
+
+        // declared length past the end of file
+        let mut rom = unif_file(&[chunk(b"MAPR", b"NES-NROM-256\0"), chunk(b"PRG0", &[0; 0x10])]);
+        rom[32 + 21 + 4..32 + 21 + 8].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+        let e = Unif::from_rom(io::Cursor::new(rom)).unwrap_err();
+        assert_eq!((e.kind(), e.field(), e.offset()), (ErrorKind::InvalidRom, Some("PRG0 chunk"), Some(32 + 21)));
+        assert_eq!((e.expected(), e.found()), (Some("4294967280 bytes"), Some("16 bytes")));
+    }
+
+    #[test]
+    fn rom_chunks_are_written_back_split() {
+        let prg0 = vec![0x11; 0x8000];
+        let prg1 = vec![0x22; 0x4000];
+        let chr0 = vec![0x33; 0x2000];
+        let chr2 = vec![0x44; 0x2000];
+        let crc = |data: &[u8]| crc32::crc32_ieee_from_slice(data).to_le_bytes().to_vec();
+        let rom = unif_file(&[
+            chunk(b"MAPR", b"NES-TLROM\0"),
+            chunk(b"PCK0", &crc(&prg0)),
+            chunk(b"PRG0", &prg0),
+            chunk(b"PCK1", &crc(&prg1)),
+            chunk(b"PRG1", &prg1),
+            chunk(b"CCK0", &crc(&chr0)),
+            chunk(b"CHR0", &chr0),
+            chunk(b"CCK2", &crc(&chr2)),
+            chunk(b"CHR2", &chr2),
+        ]);
+
+        let unif = Unif::from_rom(io::Cursor::new(&rom)).unwrap();
+        assert_eq!(unif.prg_chunk_sizes[0..3], [0x8000, 0x4000, 0]);
+        assert_eq!(unif.chr_chunk_sizes[0..3], [0x2000, 0, 0x2000]);
+        assert!(unif.to_bytes().unwrap() == rom);
+
+        // sizes that no longer match the data fall back to a single chunk
+        let mut edited = unif.clone();
+        edited.prg_data.truncate(0x8000);
+        let written = Unif::from_rom(io::Cursor::new(edited.to_bytes().unwrap())).unwrap();
+        assert_eq!(written.prg_chunk_sizes[0..2], [0x8000, 0]);
+        assert_eq!(written.chr_chunk_sizes, unif.chr_chunk_sizes);
+    }
+
+    #[test]
+    fn preferred_boards_match_their_mapper() {
+        for (mapper, submapper, board) in UNIF_PREFERRED_BOARDS.iter() {
+            let info = UNIF_BOARD_MAPPINGS.get(board).unwrap();
+            assert_eq!(info.0, *mapper, "{}", board);
+            assert!(info.1 == *submapper || *submapper == 0, "{}", board);
+        }
+    }
+
+    #[test]
+    fn example_roms_round_trip() {
+        let mut checked = 0;
+        for entry in std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/example_roms")).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "unf" && ext != "unif") {
+                continue;
+            }
+
+            let unif = Unif::from_rom(std::fs::File::open(&path).unwrap()).unwrap();
+            let written = Unif::from_rom(io::Cursor::new(unif.to_bytes().unwrap())).unwrap();
+            assert_eq!(written, unif, "{}", path.display());
+            checked += 1;
         }
-}
\ No newline at end of file
+
+        assert!(checked > 0);
+    }
+}